@@ -1,4 +1,8 @@
 pub mod parser;
+pub mod pattern;
+pub mod precedence;
 pub mod value;
 
-pub use value::Statement;
+pub use pattern::{Pattern, PatternKind};
+pub use precedence::{Associativity, PrecedenceRef, PrecedenceTable};
+pub use value::{AssignKind, Statement};
@@ -1,12 +1,22 @@
-use super::value::{Expression, Statement};
+use super::pattern::Pattern;
+use super::precedence::{PrecedenceRef, PrecedenceTable};
+use super::value::{AssignKind, Expression, ExpressionKind, Statement};
 use crate::ast::value::LiteralValue;
-use crate::ast::value::Statement::Expr;
-use crate::{Token, TokenKind};
-use anyhow::{anyhow, bail, Context};
+use crate::diagnostic::Diagnostic;
+use crate::lexer::{FormatPart, Span, Token, TokenKind, Tokenizer};
+use crate::xurvm::Number;
 use std::collections::VecDeque;
 
+const CONST_KEYWORD: &str = "const";
+const NONLOCAL_KEYWORD: &str = "nonlocal";
+/// Inline functions whose right hand side is a single method call rather than a whole
+/// expression, so `a.b(1).c` chains left to right.
+const METHOD_CALL_FNS: [&str; 2] = [".", "->"];
+
 pub struct Parser {
     tokens: VecDeque<Token>,
+    last_span: Span,
+    precedence: PrecedenceRef,
 }
 
 impl Iterator for Parser {
@@ -22,117 +32,417 @@ impl Iterator for Parser {
 }
 
 impl Parser {
+    /// Whitespace and comments are dropped, the rest of `tokens` is parsed in order.
     pub fn new(tokens: Vec<Token>) -> Self {
         Self {
-            tokens: VecDeque::from(tokens),
+            tokens: tokens
+                .into_iter()
+                .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment(_)))
+                .collect(),
+            last_span: Span::default(),
+            precedence: PrecedenceTable::new_ref(),
         }
     }
 
+    /// Parses with a table that can change between statements, such as the vm's.
+    pub fn with_precedence(mut self, precedence: PrecedenceRef) -> Self {
+        self.precedence = precedence;
+        self
+    }
+
     pub fn next_statement(&mut self) -> anyhow::Result<Statement> {
-        let stmnt = match self.consume().context("Unexpected end of statement")? {
-            TokenKind::Identifier(id) => self.ident(id)?,
-            TokenKind::OpenBracket => Statement::Expr(self.array()?),
-            TokenKind::String(s) => {
-                let ex = Expression::Literal(LiteralValue::String(s.into()));
-                Statement::Expr(self.join_expr_modifiers(ex)?)
-            }
-            TokenKind::Numeric(s) => {
-                let ex = Expression::Literal(LiteralValue::Number(s));
-                Statement::Expr(self.join_expr_modifiers(ex)?)
-            }
-            TokenKind::OpenParen => Statement::Expr(self.paren_list()?),
-            TokenKind::Symbol(s) => {
-                let c = Expression::Call {
-                    callie: Box::from(Expression::Variable(String::from("__get_symbol_bind__"))),
-                    args: vec![Expression::Literal(LiteralValue::String(s.into()))],
+        match self.peek() {
+            Some(TokenKind::Semicolon | TokenKind::Newline) => {
+                let span = self.consume().expect("peeked a separator").span;
+                return Ok(Statement::Empty(span));
+            }
+            Some(TokenKind::Identifier(id)) if id == CONST_KEYWORD => {
+                let span = self.consume().expect("peeked an identifier").span;
+                return self.keyword_assignment(CONST_KEYWORD, span, AssignKind::Const);
+            }
+            Some(TokenKind::Identifier(id)) if id == NONLOCAL_KEYWORD => {
+                let span = self.consume().expect("peeked an identifier").span;
+                return self.keyword_assignment(NONLOCAL_KEYWORD, span, AssignKind::Nonlocal);
+            }
+            _ => {}
+        }
+
+        let ex = self.expression()?;
+        if let Some(TokenKind::Equals) = self.peek() {
+            return self.assignment(ex.span, ex, AssignKind::Plain);
+        }
+        Ok(Statement::Expr(ex))
+    }
+
+    fn expression(&mut self) -> anyhow::Result<Expression> {
+        self.expression_bp(0)
+    }
+
+    /// Parses inline calls with a Pratt parser, stopping at any inline function that
+    /// binds more loosely than `min_bp`. Calls with a parameter set and method calls
+    /// always bind to the operand right before them.
+    fn expression_bp(&mut self, min_bp: u16) -> anyhow::Result<Expression> {
+        let mut lhs = self.operand()?;
+        loop {
+            match self.peek() {
+                Some(TokenKind::OpenParen) => {
+                    let open = self.consume().expect("peeked a paren").span;
+                    lhs = self.paren_list_fn_call(open, lhs)?;
+                }
+                Some(TokenKind::Identifier(id)) if METHOD_CALL_FNS.contains(&id.as_str()) => {
+                    let (id, span) = self.consume_ident();
+                    let rhs = self.method(&id, span)?;
+                    lhs = Self::inline_fn_call(id, span, lhs, rhs);
+                }
+                Some(TokenKind::Identifier(id)) => {
+                    let (left_bp, right_bp) = self.precedence.borrow().binding_power(id);
+                    if left_bp < min_bp {
+                        break;
+                    }
+                    let (id, span) = self.consume_ident();
+                    // `count x2`, with nothing for a right hand side. An operator from the
+                    // precedence table at the end of a line takes it from the next line.
+                    if self.at_expression_end()
+                        && !(self.precedence.borrow().contains(&id)
+                            && matches!(self.peek(), Some(TokenKind::Newline)))
+                    {
+                        let callie = Expression::new(ExpressionKind::Variable(id), span);
+                        let span = lhs.span.to(span);
+                        let args = vec![lhs];
+                        lhs = Expression::new(
+                            ExpressionKind::Call {
+                                callie: Box::from(callie),
+                                args,
+                            },
+                            span,
+                        );
+                        continue;
+                    }
+                    let rhs = self.expression_bp(right_bp)?;
+                    lhs = Self::inline_fn_call(id, span, lhs, rhs);
+                }
+                _ => break,
+            }
+        }
+        Ok(lhs)
+    }
+
+    /// A single value, without any calls after it.
+    fn operand(&mut self) -> anyhow::Result<Expression> {
+        // An expression can carry on from the line before, e.g. after an operator.
+        self.skip_newlines();
+        let token = self.consume().ok_or_else(|| {
+            Diagnostic::error("Unexpected end of statement", self.last_span.after())
+                .with_primary_label("expected an expression")
+        })?;
+        let span = token.span;
+        let ex = match token.kind {
+            // `-1` is a literal rather than a call to `-` with a missing left side.
+            TokenKind::Identifier(id) if id == "-" && self.number_follows(span) => {
+                let number = self.consume().expect("peeked a number");
+                let n = match number.kind {
+                    TokenKind::Numeric(n) => Number::from(-n),
+                    TokenKind::Float(n) => Number::from(-n),
+                    _ => unreachable!("peeked a number"),
+                };
+                let n = LiteralValue::Number(n);
+                Expression::new(ExpressionKind::Literal(n), span.to(number.span))
+            }
+            // `-x` negates whatever follows, while a lone `-` is still the function itself.
+            TokenKind::Identifier(id) if id == "-" && !self.at_expression_end() => {
+                let inner = self.operand()?;
+                let callie = Expression::new(ExpressionKind::Variable("neg".into()), span);
+                let span = span.to(inner.span);
+                Expression::new(
+                    ExpressionKind::Call {
+                        callie: Box::from(callie),
+                        args: vec![inner],
+                    },
+                    span,
+                )
+            }
+            TokenKind::Identifier(id) => Expression::new(ExpressionKind::Variable(id), span),
+            TokenKind::OpenBracket => self.array(span)?,
+            // `..rest`, or just `..` in a pattern
+            TokenKind::DotDot => {
+                let inner = match self.peek() {
+                    Some(TokenKind::Comma | TokenKind::CloseBracket) => {
+                        Expression::none(span.after())
+                    }
+                    _ => self.operand()?,
                 };
-                Statement::Expr(self.join_expr_modifiers(c)?)
+                let span = span.to(inner.span);
+                Expression::new(ExpressionKind::Spread(Box::new(inner)), span)
+            }
+            TokenKind::String(s) => Expression::new(
+                ExpressionKind::Literal(LiteralValue::String(s.into())),
+                span,
+            ),
+            TokenKind::Format(parts) => self.format(parts, span)?,
+            TokenKind::Bytes(b) => {
+                Expression::new(ExpressionKind::Literal(LiteralValue::Bytes(b.into())), span)
+            }
+            TokenKind::Numeric(n) => {
+                let n = LiteralValue::Number(Number::from(n));
+                Expression::new(ExpressionKind::Literal(n), span)
+            }
+            TokenKind::Float(n) => {
+                let n = LiteralValue::Number(Number::from(n));
+                Expression::new(ExpressionKind::Literal(n), span)
+            }
+            TokenKind::Boolean(b) => {
+                Expression::new(ExpressionKind::Literal(LiteralValue::Boolean(b)), span)
+            }
+            TokenKind::None => Expression::new(ExpressionKind::Literal(LiteralValue::None), span),
+            TokenKind::OpenParen => self.paren_list(span)?,
+            TokenKind::OpenBrace if self.map_follows() => self.map(span)?,
+            TokenKind::OpenBrace => self.block(span)?,
+            TokenKind::At if matches!(self.peek(), Some(TokenKind::OpenBrace)) => {
+                self.consume();
+                self.map(span)?
+            }
+            TokenKind::At if matches!(self.peek(), Some(TokenKind::OpenParen)) => {
+                let open = self.consume().expect("peeked a paren").span;
+                self.tuple(span, open)?
+            }
+            TokenKind::Symbol(s) => Expression::new(
+                ExpressionKind::Call {
+                    callie: Box::from(Expression::new(
+                        ExpressionKind::Variable(String::from("__get_symbol_bind__")),
+                        span,
+                    )),
+                    args: vec![Expression::new(
+                        ExpressionKind::Literal(LiteralValue::String(s.into())),
+                        span,
+                    )],
+                },
+                span,
+            ),
+            TokenKind::Unknown(s) if s == "\"" || s == "\"\"\"" => {
+                return Err(Diagnostic::error("Unterminated string", span)
+                    .with_primary_label(format!("expected a closing `{}`", s))
+                    .into())
+            }
+            TokenKind::Unknown(s) if s.starts_with('\\') => {
+                return Err(Diagnostic::error(format!("Unknown escape `{}`", s), span)
+                    .with_primary_label("in this string")
+                    .with_note(
+                        r#"valid escapes are \n \t \r \0 \\ \" \u{...}, and \xff in byte strings"#,
+                    )
+                    .into())
+            }
+            TokenKind::Unknown(s) if s == "{" => {
+                return Err(Diagnostic::error("Unclosed `{` in format string", span)
+                    .with_primary_label("expected a closing `}` on this line")
+                    .into())
+            }
+            TokenKind::Unknown(s) if s == "}" => {
+                return Err(Diagnostic::error("Unmatched `}` in format string", span)
+                    .with_primary_label("in this string")
+                    .with_note("use `}}` for a literal brace")
+                    .into())
+            }
+            TokenKind::Unknown(s) if s == "#[" => {
+                return Err(Diagnostic::error("Unterminated block comment", span)
+                    .with_primary_label("expected a closing `]#`")
+                    .into())
+            }
+            t => {
+                return Err(Diagnostic::error(format!("Unexpected token {:?}", t), span)
+                    .with_primary_label("expected an expression")
+                    .into())
+            }
+        };
+        Ok(ex)
+    }
+
+    // const x = 10
+    // const x = 10
+    fn keyword_assignment(
+        &mut self,
+        keyword: &str,
+        keyword_span: Span,
+        kind: AssignKind,
+    ) -> anyhow::Result<Statement> {
+        let target = self.expression()?;
+        if !matches!(self.peek(), Some(TokenKind::Equals)) {
+            return Err(Diagnostic::error(
+                format!("Expected `=` after `{}` binding", keyword),
+                self.last_span.after(),
+            )
+            .with_primary_label("expected `=`")
+            .with_label(keyword_span, "binding starts here")
+            .into());
+        }
+        self.assignment(keyword_span, target, kind)
+    }
+
+    // x = 10
+    fn assignment(
+        &mut self,
+        start: Span,
+        target: Expression,
+        kind: AssignKind,
+    ) -> anyhow::Result<Statement> {
+        let equals = self.consume().expect("peeked an equals").span;
+        let target = match target.kind {
+            ExpressionKind::Variable(_)
+            | ExpressionKind::Array(_)
+            | ExpressionKind::ParenList(_)
+            | ExpressionKind::Tuple(_)
+            | ExpressionKind::Map(_) => Pattern::from_expression(&target)?,
+            _ => {
+                return Err(Diagnostic::error("Invalid assignment target", target.span)
+                    .with_primary_label("cannot assign to this expression")
+                    .with_label(equals, "assignment here")
+                    .into())
             }
-            TokenKind::Semicolon => Statement::Empty,
-            t => bail!("Unexpected token {:?}", t),
         };
-        Ok(stmnt)
+        let value = self.expression()?;
+        Ok(Statement::Assign {
+            span: start.to(value.span),
+            target,
+            value,
+            kind,
+        })
     }
 
     fn peek(&self) -> Option<&TokenKind> {
         self.tokens.front().map(|t| &t.kind)
     }
+    /// True if a number starts right where `span` ends, with no space between.
+    fn number_follows(&self, span: Span) -> bool {
+        matches!(
+            self.tokens.front(),
+            Some(Token {
+                kind: TokenKind::Numeric(_) | TokenKind::Float(_),
+                span: next,
+            }) if next.start == span.end
+        )
+    }
+    fn skip_newlines(&mut self) {
+        while let Some(TokenKind::Newline) = self.peek() {
+            self.consume();
+        }
+    }
+    /// True if nothing more can follow in the current expression.
+    fn at_expression_end(&self) -> bool {
+        matches!(
+            self.peek(),
+            None | Some(
+                TokenKind::Newline
+                    | TokenKind::Semicolon
+                    | TokenKind::Comma
+                    | TokenKind::CloseParen
+                    | TokenKind::CloseBracket
+                    | TokenKind::CloseBrace
+            )
+        )
+    }
     fn is_eof(&self) -> bool {
         self.tokens.is_empty()
     }
-    fn consume(&mut self) -> Option<TokenKind> {
-        self.tokens.pop_front().map(|t| t.kind)
+    fn consume(&mut self) -> Option<Token> {
+        let token = self.tokens.pop_front()?;
+        self.last_span = token.span;
+        Some(token)
+    }
+    fn consume_ident(&mut self) -> (String, Span) {
+        match self.consume() {
+            Some(Token {
+                kind: TokenKind::Identifier(id),
+                span,
+            }) => (id, span),
+            _ => unreachable!("peeked an identifier"),
+        }
     }
+
     // x mod 3
     // `mod` is the inline function
-    fn inline_fn_call(&mut self, ident: String, lp: Expression) -> anyhow::Result<Expression> {
-        let fn_ex = match self.next_statement() {
-            Ok(Statement::Expr(e)) => Expression::Call {
-                callie: Box::from(Expression::Variable(ident)),
+    fn inline_fn_call(
+        ident: String,
+        ident_span: Span,
+        lp: Expression,
+        e: Expression,
+    ) -> Expression {
+        let span = lp.span.to(e.span);
+        let callie = Expression::new(ExpressionKind::Variable(ident), ident_span);
+        Expression::new(
+            ExpressionKind::Call {
+                callie: Box::from(callie),
                 args: vec![lp, e],
             },
-            e => {
-                bail!(
-                    "Expected expression for right argument of {}! got {:?}",
-                    ident,
-                    e?
-                )
-            }
-        };
-
-        let ex = self.join_expr_modifiers(fn_ex)?;
-        Ok(ex)
+            span,
+        )
     }
 
-    fn paren_list_fn_call(&mut self, lp: Expression) -> anyhow::Result<Expression> {
-        let exprs = self.comma_seprated_list(&TokenKind::CloseParen)?;
-
-        let fn_ex = Expression::Call {
-            callie: Box::from(lp),
-            args: exprs,
+    // the `to_int(16)` in "ff".to_int(16)
+    fn method(&mut self, call_fn: &str, call_fn_span: Span) -> anyhow::Result<Expression> {
+        let name = match self.consume() {
+            Some(Token {
+                kind: TokenKind::Identifier(name),
+                span,
+            }) => Expression::new(ExpressionKind::Variable(name), span),
+            token => {
+                let span = token.map_or(self.last_span.after(), |t| t.span);
+                return Err(Diagnostic::error(
+                    format!("Expected a function name after `{}`", call_fn),
+                    span,
+                )
+                .with_primary_label("expected a name")
+                .with_label(call_fn_span, "method call here")
+                .into());
+            }
         };
 
-        let ex = self.join_expr_modifiers(fn_ex)?;
-        Ok(ex)
-    }
-
-    fn join_expr_modifiers(&mut self, ex: Expression) -> anyhow::Result<Expression> {
-        let mut ex = ex;
-        loop {
-            let peek_value = match self.peek() {
-                Some(v) => v,
-                None => break,
-            };
-
-            match peek_value {
-                TokenKind::Identifier(id) => {
-                    let ident = match self.consume().context("Failed to read identifier")? {
-                        TokenKind::Identifier(s) => s,
-                        _ => bail!("failed to read identifier"),
-                    };
-                    ex = self.inline_fn_call(ident, ex)?;
-                }
-                TokenKind::OpenParen => {
-                    self.consume();
-                    ex = self.paren_list_fn_call(ex)?;
-                }
-                _ => break,
-            };
+        if let Some(TokenKind::OpenParen) = self.peek() {
+            let open = self.consume().expect("peeked a paren").span;
+            let args = self.comma_seprated_list(open, &TokenKind::CloseParen)?;
+            let span = name.span.to(self.last_span);
+            return Ok(Expression::new(
+                ExpressionKind::Call {
+                    callie: Box::new(name),
+                    args,
+                },
+                span,
+            ));
         }
-        Ok(ex)
+        Ok(name)
     }
 
-    fn ident(&mut self, id: String) -> anyhow::Result<Statement> {
-        let ex = self.join_expr_modifiers(Expression::Variable(id))?;
-        Ok(Statement::Expr(ex))
+    fn paren_list_fn_call(&mut self, open: Span, lp: Expression) -> anyhow::Result<Expression> {
+        let exprs = self.comma_seprated_list(open, &TokenKind::CloseParen)?;
+        let span = lp.span.to(self.last_span);
+
+        Ok(Expression::new(
+            ExpressionKind::Call {
+                callie: Box::from(lp),
+                args: exprs,
+            },
+            span,
+        ))
     }
 
-    fn comma_seprated_list(&mut self, terminator: &TokenKind) -> anyhow::Result<Vec<Expression>> {
+    fn comma_seprated_list(
+        &mut self,
+        open: Span,
+        terminator: &TokenKind,
+    ) -> anyhow::Result<Vec<Expression>> {
         let mut exprs: Vec<Expression> = Vec::new();
-        let mut last_expression = Some(Expression::None);
+        let mut last_expression = Some(Expression::none(self.last_span.after()));
 
         loop {
-            let peek_value = self.peek().context("eof on list")?;
+            self.skip_newlines();
+            let peek_value = self.peek().ok_or_else(|| {
+                let close = match terminator {
+                    TokenKind::CloseParen => ")",
+                    TokenKind::CloseBracket => "]",
+                    _ => "end of list",
+                };
+                Diagnostic::error("Unexpected end of list", self.last_span.after())
+                    .with_primary_label(format!("expected `{}`", close))
+                    .with_label(open, "list starts here")
+            })?;
             match peek_value {
                 v if v == terminator => {
                     if let Some(e) = last_expression {
@@ -142,44 +452,187 @@ impl Parser {
                     break;
                 }
                 TokenKind::Comma => {
-                    if let Some(e) = std::mem::replace(&mut last_expression, Some(Expression::None))
+                    self.consume();
+                    if let Some(e) =
+                        last_expression.replace(Expression::none(self.last_span.after()))
                     {
                         exprs.push(e);
                     }
-
-                    self.consume();
                     continue;
                 }
                 _ => {}
             }
-            match self.next_statement().context(format!(
-                "Unexpected end of {:?} terminated list. exprs({:?})",
-                terminator, exprs
-            ))? {
-                Statement::Expr(e) => last_expression = Some(e),
-                s => bail!("Invalid value in array! {:?}", s),
-            };
+            last_expression = Some(self.expression()?);
         }
         Ok(exprs)
     }
-    fn array(&mut self) -> anyhow::Result<Expression> {
-        let exprs = self
-            .comma_seprated_list(&TokenKind::CloseBracket)
-            .context("Failed to read list")?;
+    fn array(&mut self, open: Span) -> anyhow::Result<Expression> {
+        let mut exprs = self.comma_seprated_list(open, &TokenKind::CloseBracket)?;
+        // `[]` and trailing commas leave an empty slot at the end.
+        if exprs.last().is_some_and(|e| e.is_none()) {
+            exprs.pop();
+        }
+        let span = open.to(self.last_span);
 
-        let ex = self
-            .join_expr_modifiers(Expression::Array(exprs))
-            .context(format!("Failed to join exprs for list"))?;
-        Ok(ex)
+        Ok(Expression::new(ExpressionKind::Array(exprs), span))
+    }
+    // f"{name}: {count x2}"
+    fn format(&mut self, parts: Vec<FormatPart>, span: Span) -> anyhow::Result<Expression> {
+        let mut exprs = Vec::new();
+        for part in parts {
+            let ex = match part {
+                FormatPart::Text(s) => Expression::new(
+                    ExpressionKind::Literal(LiteralValue::String(s.into())),
+                    span,
+                ),
+                FormatPart::Hole(source, at) => {
+                    let mut parser = Parser::new(Tokenizer::at(&source, at).collect())
+                        .with_precedence(self.precedence.clone());
+                    if parser.is_eof() {
+                        return Err(Diagnostic::error("Empty `{}` in format string", span)
+                            .with_label(at, "expected an expression")
+                            .with_note("use `{{` for a literal brace")
+                            .into());
+                    }
+                    let ex = parser.expression()?;
+                    if let Some(extra) = parser.consume() {
+                        return Err(Diagnostic::error(
+                            "Unexpected token in format string",
+                            extra.span,
+                        )
+                        .with_primary_label("expected the end of the `{}`")
+                        .into());
+                    }
+                    ex
+                }
+            };
+            exprs.push(ex);
+        }
+        Ok(Expression::new(ExpressionKind::Format(exprs), span))
     }
-    fn paren_list(&mut self) -> anyhow::Result<Expression> {
-        let exprs = self
-            .comma_seprated_list(&TokenKind::CloseParen)
-            .context("Failed to read parens")?;
 
-        let ex = self
-            .join_expr_modifiers(Expression::ParenList(exprs))
-            .context("Failed to join exprs for paren")?;
-        Ok(ex)
+    // @(1, 2)
+    fn tuple(&mut self, at: Span, open: Span) -> anyhow::Result<Expression> {
+        let mut exprs = self.comma_seprated_list(open, &TokenKind::CloseParen)?;
+        if exprs.last().is_some_and(|e| e.is_none()) {
+            exprs.pop();
+        }
+        let span = at.to(self.last_span);
+
+        Ok(Expression::new(ExpressionKind::Tuple(exprs), span))
+    }
+    fn paren_list(&mut self, open: Span) -> anyhow::Result<Expression> {
+        let exprs = self.comma_seprated_list(open, &TokenKind::CloseParen)?;
+        let span = open.to(self.last_span);
+
+        Ok(Expression::new(ExpressionKind::ParenList(exprs), span))
+    }
+
+    /// True if the brace just consumed opens a map rather than a block, which is when
+    /// it starts with a literal followed by a `:`.
+    fn map_follows(&self) -> bool {
+        let mut tokens = self
+            .tokens
+            .iter()
+            .map(|t| &t.kind)
+            .filter(|k| !matches!(k, TokenKind::Newline));
+        matches!(
+            tokens.next(),
+            Some(
+                TokenKind::String(_)
+                    | TokenKind::Numeric(_)
+                    | TokenKind::Float(_)
+                    | TokenKind::Boolean(_)
+                    | TokenKind::None
+            )
+        ) && matches!(tokens.next(), Some(TokenKind::Colon))
+    }
+
+    // {"a": 1, "b": 2}
+    fn map(&mut self, open: Span) -> anyhow::Result<Expression> {
+        let mut entries = Vec::new();
+        loop {
+            self.skip_newlines();
+            match self.peek() {
+                None => {
+                    return Err(
+                        Diagnostic::error("Unexpected end of map", self.last_span.after())
+                            .with_primary_label("expected `}`")
+                            .with_label(open, "map starts here")
+                            .into(),
+                    )
+                }
+                Some(TokenKind::CloseBrace) => {
+                    self.consume();
+                    break;
+                }
+                _ => {}
+            }
+
+            let key = self.expression()?;
+            self.skip_newlines();
+            if !matches!(self.peek(), Some(TokenKind::Colon)) {
+                let span = self
+                    .tokens
+                    .front()
+                    .map_or(self.last_span.after(), |t| t.span);
+                return Err(Diagnostic::error("Expected `:` after a map key", span)
+                    .with_primary_label("expected `:`")
+                    .with_label(key.span, "key")
+                    .into());
+            }
+            self.consume();
+            let value = self.expression()?;
+            entries.push((key, value));
+
+            self.skip_newlines();
+            match self.peek() {
+                Some(TokenKind::Comma) => {
+                    self.consume();
+                }
+                Some(TokenKind::CloseBrace) | None => {}
+                Some(_) => {
+                    let span = self.tokens.front().expect("peeked a token").span;
+                    return Err(
+                        Diagnostic::error("Expected `,` or `}` after a map entry", span)
+                            .with_primary_label("expected `,` or `}`")
+                            .with_label(open, "map starts here")
+                            .into(),
+                    );
+                }
+            }
+        }
+        let span = open.to(self.last_span);
+
+        Ok(Expression::new(ExpressionKind::Map(entries), span))
+    }
+
+    // { a = 1; a + 1 }
+    fn block(&mut self, open: Span) -> anyhow::Result<Expression> {
+        let mut statements = Vec::new();
+        loop {
+            match self.peek() {
+                None => {
+                    return Err(Diagnostic::error(
+                        "Unexpected end of block",
+                        self.last_span.after(),
+                    )
+                    .with_primary_label("expected `}`")
+                    .with_label(open, "block starts here")
+                    .into())
+                }
+                Some(TokenKind::CloseBrace) => {
+                    self.consume();
+                    break;
+                }
+                _ => match self.next_statement()? {
+                    Statement::Empty(_) => {}
+                    s => statements.push(s),
+                },
+            }
+        }
+        let span = open.to(self.last_span);
+
+        Ok(Expression::new(ExpressionKind::Block(statements), span))
     }
 }
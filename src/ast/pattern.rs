@@ -0,0 +1,110 @@
+use super::value::{Expression, ExpressionKind, LiteralValue};
+use crate::diagnostic::Diagnostic;
+use crate::lexer::Span;
+
+const WILDCARD: &str = "_";
+
+#[derive(Clone, Debug)]
+pub enum PatternKind {
+    /// `_`, which matches anything and binds nothing.
+    Wildcard,
+    Bind(String),
+    /// Matches values equal to the literal.
+    Literal(LiteralValue),
+    /// `[a, b, ..rest]`, or `@(a, b)` or `(a, b)`, which match arrays and tuples alike.
+    /// `rest` is the index of the `..` in `items`, and the name it binds the leftover
+    /// elements to, if any.
+    Array {
+        items: Vec<Pattern>,
+        rest: Option<(usize, Option<String>)>,
+    },
+    /// `{"a": a}`. Keys are literals and must be in the map, other keys are ignored.
+    Map(Vec<(LiteralValue, Pattern)>),
+}
+
+/// The left hand side of a destructuring assignment, or an array or map arm of `match`.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub span: Span,
+}
+
+impl Pattern {
+    pub fn new(kind: PatternKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    /// Reads a pattern back out of an expression that was parsed before we knew it was
+    /// one, such as the target of `[a, b] = ...`.
+    pub fn from_expression(ex: &Expression) -> anyhow::Result<Pattern> {
+        let kind = match &ex.kind {
+            ExpressionKind::Variable(name) if name == WILDCARD => PatternKind::Wildcard,
+            ExpressionKind::Variable(name) => PatternKind::Bind(name.clone()),
+            ExpressionKind::Literal(l) => PatternKind::Literal(l.clone()),
+            // `(a)`
+            ExpressionKind::ParenList(elements) if elements.len() == 1 => {
+                return Pattern::from_expression(&elements[0])
+            }
+            ExpressionKind::Array(elements)
+            | ExpressionKind::Tuple(elements)
+            | ExpressionKind::ParenList(elements) => Self::sequence(elements)?,
+            ExpressionKind::Map(entries) => {
+                let mut patterns = Vec::new();
+                for (key, value) in entries {
+                    let key = match &key.kind {
+                        ExpressionKind::Literal(l) => l.clone(),
+                        _ => {
+                            return Err(Diagnostic::error(
+                                "Map pattern keys must be literals",
+                                key.span,
+                            )
+                            .with_primary_label("not a literal")
+                            .into())
+                        }
+                    };
+                    patterns.push((key, Pattern::from_expression(value)?));
+                }
+                PatternKind::Map(patterns)
+            }
+            _ => {
+                return Err(Diagnostic::error("Invalid pattern", ex.span)
+                    .with_primary_label("expected a name, literal, array, tuple or map")
+                    .into())
+            }
+        };
+        Ok(Pattern::new(kind, ex.span))
+    }
+
+    // [a, b, ..rest]
+    fn sequence(elements: &[Expression]) -> anyhow::Result<PatternKind> {
+        let mut items = Vec::new();
+        let mut rest = None;
+        for element in elements {
+            match &element.kind {
+                ExpressionKind::Spread(_) if rest.is_some() => {
+                    return Err(Diagnostic::error(
+                        "Only one `..` is allowed in an array pattern",
+                        element.span,
+                    )
+                    .with_primary_label("second `..`")
+                    .into());
+                }
+                ExpressionKind::Spread(name) => {
+                    let name = match &name.kind {
+                        ExpressionKind::None => None,
+                        ExpressionKind::Variable(n) if n == WILDCARD => None,
+                        ExpressionKind::Variable(n) => Some(n.clone()),
+                        _ => {
+                            return Err(Diagnostic::error("Expected a name after `..`", name.span)
+                                .with_primary_label("not a name")
+                                .into())
+                        }
+                    };
+                    rest = Some((items.len(), name));
+                }
+                _ => items.push(Pattern::from_expression(element)?),
+            }
+        }
+        Ok(PatternKind::Array { items, rest })
+    }
+}
@@ -0,0 +1,84 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Where inline calls of functions without an entry in the table land. Named functions
+/// bind tighter than any operator, so `"FF" to_int 16 + 1` is `to_int("FF", 16) + 1`.
+pub const DEFAULT_PRECEDENCE: u8 = 11;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+pub type PrecedenceRef = Rc<RefCell<PrecedenceTable>>;
+
+/// How tightly each inline function binds its arguments. Higher binds tighter.
+/// The table is shared between the parser and the vm so `infixl` and `infixr`
+/// affect every statement parsed after them.
+#[derive(Debug)]
+pub struct PrecedenceTable {
+    ops: HashMap<String, (u8, Associativity)>,
+}
+
+impl Default for PrecedenceTable {
+    fn default() -> Self {
+        use Associativity::*;
+        let mut table = Self {
+            ops: HashMap::new(),
+        };
+        let levels: [(u8, Associativity, &[&str]); 10] = [
+            (1, Left, &["||"]),
+            (2, Left, &["&&"]),
+            (3, Left, &["==", "!=", "<", "<=", ">", ">="]),
+            (4, Left, &["|"]),
+            (5, Left, &["^"]),
+            (6, Left, &["&"]),
+            (7, Left, &["<<", ">>"]),
+            (8, Left, &["+", "-"]),
+            (9, Left, &["*", "/", "//", "mod"]),
+            (10, Right, &["**"]),
+        ];
+        for (precedence, associativity, ops) in levels {
+            for op in ops {
+                table.set(op, precedence, associativity);
+            }
+        }
+        table
+    }
+}
+
+impl PrecedenceTable {
+    pub fn new_ref() -> PrecedenceRef {
+        Rc::new(RefCell::new(Self::default()))
+    }
+
+    pub fn get(&self, name: &str) -> (u8, Associativity) {
+        self.ops
+            .get(name)
+            .copied()
+            .unwrap_or((DEFAULT_PRECEDENCE, Associativity::Left))
+    }
+
+    /// True if `name` has its own entry, rather than the default for named functions.
+    pub fn contains(&self, name: &str) -> bool {
+        self.ops.contains_key(name)
+    }
+
+    pub fn set(&mut self, name: &str, precedence: u8, associativity: Associativity) {
+        self.ops
+            .insert(name.to_string(), (precedence, associativity));
+    }
+
+    /// The left and right binding power of `name` for the Pratt parser. A left
+    /// associative function binds its right side a little tighter than its left.
+    pub fn binding_power(&self, name: &str) -> (u16, u16) {
+        let (precedence, associativity) = self.get(name);
+        let left = precedence as u16 * 2;
+        match associativity {
+            Associativity::Left => (left, left + 1),
+            Associativity::Right => (left, left),
+        }
+    }
+}
@@ -1,16 +1,19 @@
-use crate::lexer::{Token, TokenKind};
+use super::pattern::Pattern;
+use crate::lexer::Span;
+use crate::xurvm::{Number, Value};
 use std::rc::Rc;
 
 #[derive(Clone, Debug)]
 pub enum LiteralValue {
     String(Rc<String>),
-    Number(u128),
+    Bytes(Rc<Vec<u8>>),
+    Number(Number),
     Boolean(bool),
     None,
 }
 
 #[derive(Clone, Debug)]
-pub enum Expression {
+pub enum ExpressionKind {
     Variable(String),
     Literal(LiteralValue),
     Call {
@@ -18,25 +21,94 @@ pub enum Expression {
         args: Vec<Expression>,
     },
     Array(Vec<Expression>),
+    /// `(a, b)`, a parameter set. Expanded into the arguments when passed to a function.
+    /// As a value only `(a)` makes sense, which is just `a`.
     ParenList(Vec<Expression>),
+    /// `@(a, b)`
+    Tuple(Vec<Expression>),
+    /// `{"a": 1}` or `@{ key: value }`, in the order written.
+    Map(Vec<(Expression, Expression)>),
+    /// `f"{a}: {b}"`, the text and holes in order.
+    Format(Vec<Expression>),
+    /// `..xs` inside an array. The inner expression is `None` for a bare `..`.
+    Spread(Box<Expression>),
+    Block(Vec<Statement>),
+    /// An argument that has already been evaluated, such as one bound into a partial function.
+    Value(Value),
     None,
 }
 
+#[derive(Clone, Debug)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
 impl Expression {
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    /// An empty argument slot, such as the first argument in `to_int(,16)`.
+    pub fn none(span: Span) -> Self {
+        Self::new(ExpressionKind::None, span)
+    }
+
+    pub fn is_none(&self) -> bool {
+        matches!(self.kind, ExpressionKind::None)
+    }
+
     pub fn short_name(&self) -> String {
-        match self {
-            Expression::Variable(s) => format!("Expression(Var({}))", s),
-            Expression::Literal(s) => format!("Expression({:?})", s),
-            Expression::Call { callie, .. } => format!("Expression(Call({:?}))", callie),
-            Expression::Array(s) => format!("Expression(Array({:?}))", s),
-            Expression::ParenList(s) => format!("Expression(ParenList({:?}))", s),
-            Expression::None => format!("Expression(None)"),
+        match &self.kind {
+            ExpressionKind::Variable(s) => format!("Expression(Var({}))", s),
+            ExpressionKind::Literal(s) => format!("Expression({:?})", s),
+            ExpressionKind::Call { callie, .. } => {
+                format!("Expression(Call({}))", callie.short_name())
+            }
+            ExpressionKind::Array(s) => format!("Expression(Array({}))", s.len()),
+            ExpressionKind::ParenList(s) => format!("Expression(ParenList({}))", s.len()),
+            ExpressionKind::Tuple(s) => format!("Expression(Tuple({}))", s.len()),
+            ExpressionKind::Map(s) => format!("Expression(Map({}))", s.len()),
+            ExpressionKind::Format(s) => format!("Expression(Format({}))", s.len()),
+            ExpressionKind::Spread(s) => format!("Expression(Spread({}))", s.short_name()),
+            ExpressionKind::Block(s) => format!("Expression(Block({}))", s.len()),
+            ExpressionKind::Value(v) => format!("Expression({:?})", v),
+            ExpressionKind::None => "Expression(None)".to_string(),
         }
     }
 }
 
+/// How an assignment binds its names.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssignKind {
+    /// `x = 1` rebinds `x` in the current function, or defines it here.
+    Plain,
+    /// `const x = 1`
+    Const,
+    /// `nonlocal x = 1` rebinds an existing `x`, even one outside the current function.
+    Nonlocal,
+}
+
 #[derive(Clone, Debug)]
 pub enum Statement {
     Expr(Expression),
-    Empty,
+    /// `name = value`, `const name = value` or `nonlocal name = value`. The target can
+    /// also destructure, as in `[a, ..rest] = value`.
+    Assign {
+        target: Pattern,
+        value: Expression,
+        kind: AssignKind,
+        span: Span,
+    },
+    Empty(Span),
+}
+
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::Expr(e) => e.span,
+            Statement::Assign { span, .. } => *span,
+            Statement::Empty(span) => *span,
+        }
+    }
 }
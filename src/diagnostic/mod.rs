@@ -0,0 +1,129 @@
+mod render;
+
+pub use render::{Renderer, Source};
+
+use crate::lexer::Span;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// An error (or warning) that knows which part of the source caused it.
+///
+/// Diagnostics travel through the interpreter inside `anyhow::Error`, so code that only
+/// has a message can keep using `bail!`. [`Diagnostic::attach`] gives those a span on the
+/// way out, and [`Diagnostic::from_error`] digs the diagnostic back out for rendering.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub primary: Label,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: impl Into<String>, span: Span) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            primary: Label {
+                span,
+                message: String::new(),
+            },
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Error, message, span)
+    }
+
+    pub fn warning(message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Warning, message, span)
+    }
+
+    /// Text printed next to the `^^^` under the primary span.
+    pub fn with_primary_label(mut self, message: impl Into<String>) -> Self {
+        self.primary.message = message.into();
+        self
+    }
+
+    /// A secondary span, underlined with `---`.
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Gives `err` a location if nothing inside it has one yet.
+    pub fn attach(err: anyhow::Error, span: Span) -> anyhow::Error {
+        if err.chain().any(|e| e.is::<Diagnostic>()) {
+            return err;
+        }
+        let mut diagnostic = Diagnostic::error(err.root_cause().to_string(), span);
+        diagnostic.notes = context_notes(&err);
+        diagnostic.into()
+    }
+
+    /// Pulls the innermost diagnostic out of `err`. Any context added on top of it
+    /// becomes a note, outermost last.
+    pub fn from_error(err: &anyhow::Error) -> Option<Diagnostic> {
+        let mut diagnostic = err
+            .chain()
+            .filter_map(|e| e.downcast_ref::<Diagnostic>())
+            .last()?
+            .clone();
+        diagnostic.notes.extend(context_notes(err));
+        Some(diagnostic)
+    }
+}
+
+fn context_notes(err: &anyhow::Error) -> Vec<String> {
+    let mut notes: Vec<String> = err
+        .chain()
+        .take_while(|e| !e.is::<Diagnostic>())
+        .map(|e| e.to_string())
+        .collect();
+    if err.chain().all(|e| !e.is::<Diagnostic>()) {
+        // The root cause is the message itself.
+        notes.pop();
+    }
+    notes.reverse();
+    notes
+}
@@ -0,0 +1,168 @@
+use std::fmt::Write;
+
+use super::{Diagnostic, Label, Severity};
+use crate::lexer::Span;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const GREEN: &str = "\x1b[1;32m";
+const BLUE: &str = "\x1b[1;34m";
+
+/// The text a diagnostic's spans point into.
+pub struct Source<'a> {
+    pub name: &'a str,
+    pub text: &'a str,
+    /// Where `text` starts in the spans pointing into it, for text tokenized with
+    /// [`crate::lexer::Tokenizer::at`].
+    pub offset: usize,
+}
+
+impl<'a> Source<'a> {
+    pub fn new(name: &'a str, text: &'a str) -> Self {
+        Self {
+            name,
+            text,
+            offset: 0,
+        }
+    }
+
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// True if `span` points into this source.
+    pub fn contains(&self, span: Span) -> bool {
+        self.offset <= span.start && span.end <= self.offset + self.text.len()
+    }
+
+    /// The byte offset and text of a 1-based line.
+    fn line(&self, n: usize) -> Option<(usize, &'a str)> {
+        let mut offset = self.offset;
+        for (i, line) in self.text.split('\n').enumerate() {
+            if i + 1 == n {
+                return Some((offset, line.strip_suffix('\r').unwrap_or(line)));
+            }
+            offset += line.len() + 1;
+        }
+        None
+    }
+}
+
+/// Renders diagnostics in the style of rustc. With `color` off the output is plain
+/// text, which is what tests snapshot.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Renderer {
+    pub color: bool,
+}
+
+impl Renderer {
+    pub fn plain() -> Self {
+        Self { color: false }
+    }
+
+    pub fn colored() -> Self {
+        Self { color: true }
+    }
+
+    fn paint(&self, color: &str, text: &str) -> String {
+        if self.color && !text.is_empty() {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic, source: &Source) -> String {
+        let mut out = String::new();
+        let severity_color = match diagnostic.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+            Severity::Note => GREEN,
+        };
+
+        let _ = writeln!(
+            out,
+            "{}{}",
+            self.paint(severity_color, &diagnostic.severity.to_string()),
+            self.paint(BOLD, &format!(": {}", diagnostic.message))
+        );
+
+        let mut labels: Vec<(&Label, bool)> = vec![(&diagnostic.primary, true)];
+        labels.extend(diagnostic.labels.iter().map(|l| (l, false)));
+        labels.retain(|(l, _)| source.contains(l.span) && source.line(l.span.line).is_some());
+        labels.sort_by_key(|(l, _)| (l.span.line, l.span.col));
+
+        let gutter_width = labels
+            .iter()
+            .map(|(l, _)| l.span.line.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let gutter = " ".repeat(gutter_width);
+        let bar = self.paint(BLUE, "|");
+
+        // A span from some other source, like the body of a function defined in an
+        // earlier input, would give a line and column that mean nothing here.
+        let primary = diagnostic.primary.span;
+        let location = if source.contains(primary) {
+            format!("{}:{}:{}", source.name, primary.line, primary.col)
+        } else {
+            source.name.to_string()
+        };
+        let _ = writeln!(out, "{}{} {}", gutter, self.paint(BLUE, "-->"), location);
+
+        if !labels.is_empty() {
+            let _ = writeln!(out, "{} {}", gutter, bar);
+        }
+        let mut last_line = None;
+        for (label, is_primary) in labels {
+            let (line_start, line) = source
+                .line(label.span.line)
+                .expect("labels on missing lines were removed");
+            if last_line != Some(label.span.line) {
+                let number = format!("{:>width$}", label.span.line, width = gutter_width);
+                let _ = writeln!(out, "{} {} {}", self.paint(BLUE, &number), bar, line);
+                last_line = Some(label.span.line);
+            }
+
+            let start = label.span.start.saturating_sub(line_start).min(line.len());
+            let end = label.span.end.saturating_sub(line_start).min(line.len());
+            let padding = line.get(..start).unwrap_or("").chars().count();
+            let width = line.get(start..end).unwrap_or("").chars().count().max(1);
+
+            let (marker, color) = if is_primary {
+                ("^", severity_color)
+            } else {
+                ("-", BLUE)
+            };
+            let mut underline = marker.repeat(width);
+            if !label.message.is_empty() {
+                underline = format!("{} {}", underline, label.message);
+            }
+            let _ = writeln!(
+                out,
+                "{} {} {}{}",
+                gutter,
+                bar,
+                " ".repeat(padding),
+                self.paint(color, &underline)
+            );
+        }
+
+        if !diagnostic.notes.is_empty() {
+            let _ = writeln!(out, "{} {}", gutter, bar);
+        }
+        for note in &diagnostic.notes {
+            let _ = writeln!(
+                out,
+                "{} {} {}",
+                gutter,
+                self.paint(BLUE, "="),
+                self.paint(BOLD, &format!("note: {}", note))
+            );
+        }
+        out
+    }
+}
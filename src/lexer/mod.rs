@@ -1,5 +1,5 @@
 mod token;
 mod tokenizer;
 
-pub use token::{Token, TokenKind};
+pub use token::{FormatPart, Span, Token, TokenKind};
 pub use tokenizer::Tokenizer;
@@ -1,13 +1,69 @@
+use num_bigint::BigInt;
+
 pub(crate) const EOF_CHAR: char = '\0';
 
+/// A region of the source text. `start` and `end` are byte offsets,
+/// `line` and `col` are the 1-based position of `start`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl Span {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// A span covering everything from the start of `self` to the end of `other`.
+    pub fn to(self, other: Span) -> Span {
+        if other.start < self.start {
+            return other.to(self);
+        }
+        Span {
+            end: self.end.max(other.end),
+            ..self
+        }
+    }
+
+    /// A zero length span sitting just after `self`.
+    pub fn after(self) -> Span {
+        Span {
+            start: self.end,
+            col: self.col + self.len(),
+            ..self
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum TokenKind {
     Unknown(String),
     Whitespace,
+    /// Whitespace containing at least one line break. Ends a statement.
+    Newline,
+    /// The text of a `# ...` line comment or a `#[ ... ]#` block comment,
+    /// without its delimiters. Block comments nest.
+    Comment(String),
     Identifier(String),
     Symbol(String),
-    Numeric(u128),
+    Numeric(BigInt),
+    Float(f64),
+    /// `b"..."`
+    Bytes(Vec<u8>),
+    /// `true` or `false`
+    Boolean(bool),
+    /// The `none` keyword
+    None,
     String(String),
+    /// `f"..."`, split into its text and the holes to fill in.
+    Format(Vec<FormatPart>),
 
     Colon,
     At,
@@ -15,15 +71,27 @@ pub enum TokenKind {
     Semicolon,
     Plus,
     Dot,
+    /// `..`, as in `[first, ..rest]`
+    DotDot,
     Comma,
     OpenParen,
     CloseParen,
     OpenBracket,
     CloseBracket,
+    OpenBrace,
+    CloseBrace,
+}
+
+/// A piece of an `f"..."` string.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FormatPart {
+    Text(String),
+    /// The source of a `{...}` hole, and where it is.
+    Hole(String, Span),
 }
 
 #[derive(Clone, Debug)]
 pub struct Token {
     pub kind: TokenKind,
-    pub length: usize,
+    pub span: Span,
 }
@@ -1,12 +1,27 @@
-use super::token::{Token, TokenKind, EOF_CHAR};
+use super::token::{FormatPart, Span, Token, TokenKind, EOF_CHAR};
+use num_bigint::BigInt;
 use std::str::Chars;
 use unicode_xid::UnicodeXID;
 
+/// Operators lex as identifiers so they can be used like any other function.
+/// Two character operators are matched before their one character prefix.
+const OPERATORS: [&str; 21] = [
+    "->", "**", "//", "==", "!=", "<=", ">=", "&&", "||", "<<", ">>", "+", "-", "*", "/", "<", ">",
+    "!", "&", "|", "^",
+];
+
+fn is_ident_start(c: char) -> bool {
+    c == '_' || c.is_xid_start()
+}
+
 #[derive(Clone, Debug)]
 pub struct Tokenizer<'a> {
     initial_len: usize,
     chars: Chars<'a>,
     prev: char,
+    offset: usize,
+    line: usize,
+    col: usize,
 }
 
 impl<'a> Iterator for Tokenizer<'a> {
@@ -14,40 +29,78 @@ impl<'a> Iterator for Tokenizer<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.reset_len_consumed();
+        let (start, line, col) = (self.offset, self.line, self.col);
 
         let kind = match self.bump()? {
             c if c.is_whitespace() => self.whitespace(),
+            '#' if self.first() == '[' => {
+                self.bump();
+                self.block_comment()
+            }
+            '#' => {
+                let mut s = String::new();
+                self.eat_while(|c| c != '\n', Some(|c| s.push(c)));
+                TokenKind::Comment(s)
+            }
             '@' => match self.first() {
-                s if s.is_xid_start() => {
+                s if is_ident_start(s) => {
                     self.bump();
                     TokenKind::Symbol(self.ident())
                 }
                 '"' => {
                     self.bump();
-                    TokenKind::Symbol(self.string())
+                    self.string(false)
+                        .map_or_else(|unknown| unknown, TokenKind::Symbol)
                 }
                 _ => TokenKind::At,
             },
             c if c.is_numeric() => self.number(),
-            c if c.is_xid_start() => TokenKind::Identifier(self.ident()),
-            '"' => TokenKind::String(self.string()),
+            'b' if self.first() == '"' => {
+                self.bump();
+                self.bytes()
+            }
+            'f' if self.first() == '"' => {
+                self.bump();
+                self.format_string()
+            }
+            'r' if self.first() == '"' => {
+                self.bump();
+                self.string(true)
+                    .map_or_else(|unknown| unknown, TokenKind::String)
+            }
+            c if is_ident_start(c) => self.word(),
+            '"' => self
+                .string(false)
+                .map_or_else(|unknown| unknown, TokenKind::String),
 
             ':' => TokenKind::Colon,
+            '=' if self.first() == '=' => self.operator('='),
             '=' => TokenKind::Equals,
             ';' => TokenKind::Semicolon,
-            '+' => TokenKind::Identifier("+".into()), //TokenKind::Plus,
+            c if OPERATORS.iter().any(|op| op.starts_with(c)) => self.operator(c),
+            '.' if self.first() == '.' => {
+                self.bump();
+                TokenKind::DotDot
+            }
             '.' => TokenKind::Identifier(".".into()),
             '%' => TokenKind::Identifier("%".into()),
             '(' => TokenKind::OpenParen,
             ')' => TokenKind::CloseParen,
             '[' => TokenKind::OpenBracket,
             ']' => TokenKind::CloseBracket,
+            '{' => TokenKind::OpenBrace,
+            '}' => TokenKind::CloseBrace,
             ',' => TokenKind::Comma,
             c => TokenKind::Unknown(c.into()),
         };
         Some(Token {
             kind,
-            length: self.len_consumed(),
+            span: Span {
+                start,
+                end: start + self.len_consumed(),
+                line,
+                col,
+            },
         })
     }
 }
@@ -58,8 +111,22 @@ impl<'a> Tokenizer<'a> {
             initial_len: s.len(),
             chars: s.chars(),
             prev: EOF_CHAR,
+            offset: 0,
+            line: 1,
+            col: 1,
+        }
+    }
+    /// Tokenizes `s` as if it were found at `span` in a larger source, so spans point
+    /// into that source.
+    pub fn at(s: &'a str, span: Span) -> Self {
+        Self {
+            offset: span.start,
+            line: span.line,
+            col: span.col,
+            ..Self::new(s)
         }
     }
+
     pub fn nth(&self, i: usize) -> char {
         self.chars.clone().nth(i).unwrap_or(EOF_CHAR)
     }
@@ -70,6 +137,13 @@ impl<'a> Tokenizer<'a> {
 
     pub fn bump(&mut self) -> Option<char> {
         self.prev = self.chars.next()?;
+        self.offset += self.prev.len_utf8();
+        if self.prev == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
         Some(self.prev)
     }
 
@@ -106,60 +180,329 @@ impl<'a> Tokenizer<'a> {
 
     pub fn whitespace(&mut self) -> TokenKind {
         debug_assert!(self.prev.is_whitespace());
-        self.eat_while(char::is_whitespace, None::<fn(char)>);
-        TokenKind::Whitespace
+        let mut newline = self.prev == '\n';
+        self.eat_while(char::is_whitespace, Some(|c| newline |= c == '\n'));
+        if newline {
+            TokenKind::Newline
+        } else {
+            TokenKind::Whitespace
+        }
+    }
+
+    /// Everything up to the matching `]#`. An unterminated comment is `Unknown("#[")`.
+    pub fn block_comment(&mut self) -> TokenKind {
+        debug_assert!(self.prev == '[');
+        let mut s = String::new();
+        let mut depth = 1;
+        while let Some(c) = self.bump() {
+            s.push(c);
+            match c {
+                '#' if self.first() == '[' => depth += 1,
+                ']' if self.first() == '#' => depth -= 1,
+                _ => continue,
+            }
+            s.extend(self.bump());
+            if depth == 0 {
+                s.truncate(s.len() - 2);
+                return TokenKind::Comment(s);
+            }
+        }
+        TokenKind::Unknown("#[".into())
     }
 
     pub fn ident(&mut self) -> String {
-        debug_assert!(self.prev.is_xid_start());
+        debug_assert!(is_ident_start(self.prev));
         let mut s = String::new();
         s.push(self.prev);
         self.eat_while(char::is_xid_continue, Some(|c| s.push(c)));
         s
     }
+    pub fn operator(&mut self, first: char) -> TokenKind {
+        let next = self.first();
+        let op = OPERATORS
+            .iter()
+            .find(|op| {
+                let mut chars = op.chars();
+                chars.next() == Some(first) && chars.next().is_none_or(|c| c == next)
+            })
+            .expect("checked by the caller");
+        if op.len() == 2 {
+            self.bump();
+        }
+        TokenKind::Identifier(op.to_string())
+    }
+
+    /// An identifier, or one of the literal keywords.
+    pub fn word(&mut self) -> TokenKind {
+        let s = self.ident();
+        match s.as_str() {
+            "true" => TokenKind::Boolean(true),
+            "false" => TokenKind::Boolean(false),
+            "none" => TokenKind::None,
+            _ => TokenKind::Identifier(s),
+        }
+    }
+
     pub fn number(&mut self) -> TokenKind {
         debug_assert!(self.prev.is_numeric());
-        if self.prev == '0' && matches!(self.first(), 'x' | 'X') {
-            self.bump();
+        let radix = match (self.prev, self.first()) {
+            ('0', 'x' | 'X') => Some(16),
+            ('0', 'b' | 'B') => Some(2),
+            ('0', 'o' | 'O') => Some(8),
+            _ => None,
+        };
+        if let Some(radix) = radix {
+            let prefix = self.bump().expect("peeked a prefix");
             let mut s = String::new();
-            s.push(self.prev);
-            self.eat_while(|c| c.is_ascii_hexdigit(), Some(|c| s.push(c)));
+            self.eat_while(|c| c.is_digit(radix), Some(|c| s.push(c)));
 
-            if let Ok(n) = u128::from_str_radix(&s, 16) {
-                TokenKind::Numeric(n)
-            } else {
-                TokenKind::Unknown(s)
+            match BigInt::parse_bytes(s.as_bytes(), radix) {
+                Some(n) => TokenKind::Numeric(n),
+                None => TokenKind::Unknown(format!("0{}{}", prefix, s)),
             }
         } else {
             let mut s = String::new();
             s.push(self.prev);
-            self.eat_while(char::is_numeric, Some(|c| s.push(c)));
+            self.eat_while(|c| c.is_ascii_digit(), Some(|c| s.push(c)));
+
+            let mut is_float = false;
+            // `1.5`, but not the method call in `1.x2`
+            if self.first() == '.' && self.second().is_ascii_digit() {
+                is_float = true;
+                self.bump();
+                s.push('.');
+                self.eat_while(|c| c.is_ascii_digit(), Some(|c| s.push(c)));
+            }
+            // `1e3`, `1.5E-3`
+            let exponent_sign = matches!(self.second(), '+' | '-');
+            if matches!(self.first(), 'e' | 'E')
+                && (self.second().is_ascii_digit()
+                    || (exponent_sign
+                        && self
+                            .chars
+                            .clone()
+                            .nth(2)
+                            .is_some_and(|c| c.is_ascii_digit())))
+            {
+                is_float = true;
+                self.bump();
+                s.push('e');
+                if exponent_sign {
+                    s.push(self.bump().expect("peeked a sign"));
+                }
+                self.eat_while(|c| c.is_ascii_digit(), Some(|c| s.push(c)));
+            }
 
-            if let Ok(n) = s.parse() {
-                TokenKind::Numeric(n)
+            if is_float {
+                match s.parse() {
+                    Ok(n) => TokenKind::Float(n),
+                    Err(_) => TokenKind::Unknown(s),
+                }
             } else {
-                TokenKind::Unknown(s)
+                match BigInt::parse_bytes(s.as_bytes(), 10) {
+                    Some(n) => TokenKind::Numeric(n),
+                    None => TokenKind::Unknown(s),
+                }
             }
         }
     }
-    pub fn string(&mut self) -> String {
+    /// The contents of a `b"..."` literal. Characters outside of ascii are stored as utf-8,
+    /// and `\xff` escapes any byte. Like a string, it must close on the line it starts.
+    pub fn bytes(&mut self) -> TokenKind {
         debug_assert!(self.prev == '"');
-        let mut s = String::new();
+        let mut bytes = Vec::new();
+        let mut invalid_escape = None;
 
-        while let Some(c) = self.bump() {
+        loop {
+            let c = match self.bump() {
+                None | Some('\n') => return TokenKind::Unknown("\"".into()),
+                Some(c) => c,
+            };
             match c {
-                '\\' if self.first() == '"' => {
-                    s.push(c);
-                    if let Some(esc) = self.bump() {
-                        s.push(esc);
+                '"' => break,
+                '\\' if self.first() == '\n' || self.is_eof() => {
+                    return TokenKind::Unknown("\"".into())
+                }
+                '\\' => {
+                    let byte = match self.bump() {
+                        Some('x')
+                            if self.first().is_ascii_hexdigit()
+                                && self.second().is_ascii_hexdigit() =>
+                        {
+                            let hex: String = [self.first(), self.second()].iter().collect();
+                            self.bump();
+                            self.bump();
+                            u8::from_str_radix(&hex, 16).ok()
+                        }
+                        Some(c) => self.escape(c).and_then(|c| u8::try_from(c).ok()),
+                        None => None,
+                    };
+                    match byte {
+                        Some(b) => bytes.push(b),
+                        None => invalid_escape = Some(self.prev),
                     }
                 }
-                '"' => break,
-                _ => {
+                c => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            }
+        }
+        match invalid_escape {
+            None => TokenKind::Bytes(bytes),
+            Some(c) => TokenKind::Unknown(format!("\\{}", c)),
+        }
+    }
+
+    /// The character for the escape `\c`, with `c` already consumed.
+    fn escape(&mut self, c: char) -> Option<char> {
+        match c {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            '0' => Some('\0'),
+            '\\' => Some('\\'),
+            '"' => Some('"'),
+            // `\u{1F600}`
+            'u' if self.first() == '{' => {
+                self.bump();
+                let mut hex = String::new();
+                self.eat_while(|c| c.is_ascii_hexdigit(), Some(|c| hex.push(c)));
+                if self.first() != '}' || hex.is_empty() || hex.len() > 6 {
+                    return None;
+                }
+                self.bump();
+                u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32)
+            }
+            _ => None,
+        }
+    }
+
+    /// A string literal after its opening quote, or a `"""` multiline string whose
+    /// first line break is dropped. Raw strings keep their backslashes.
+    /// Errors are the `Unknown` token to report: the opening quotes if the string isn't
+    /// closed, or the first invalid escape.
+    pub fn string(&mut self, raw: bool) -> Result<String, TokenKind> {
+        debug_assert!(self.prev == '"');
+        let multiline = self.first() == '"' && self.second() == '"';
+        let quotes = if multiline {
+            self.bump();
+            self.bump();
+            if self.first() == '\n' {
+                self.bump();
+            }
+            "\"\"\""
+        } else {
+            "\""
+        };
+        let mut s = String::new();
+        let mut invalid_escape = None;
+
+        loop {
+            match self.bump() {
+                None => return Err(TokenKind::Unknown(quotes.into())),
+                Some('\n') if !multiline => return Err(TokenKind::Unknown(quotes.into())),
+                Some('"') if !multiline => break,
+                Some('"') if self.first() == '"' && self.second() == '"' => {
+                    self.bump();
+                    self.bump();
+                    break;
+                }
+                Some('\\') if !raw && !multiline && (self.first() == '\n' || self.is_eof()) => {
+                    return Err(TokenKind::Unknown(quotes.into()))
+                }
+                Some('\\') if !raw => {
+                    let escaped = self.bump();
+                    match escaped.and_then(|c| self.escape(c)) {
+                        Some(c) => s.push(c),
+                        None => {
+                            invalid_escape.get_or_insert(escaped.unwrap_or_default());
+                        }
+                    }
+                }
+                Some(c) => s.push(c),
+            }
+        }
+        match invalid_escape {
+            None => Ok(s),
+            Some(c) => Err(TokenKind::Unknown(format!("\\{}", c))),
+        }
+    }
+
+    /// The parts of an `f"..."` string after its opening quote. `{{` and `}}` are
+    /// literal braces. Errors are as for [`Tokenizer::string`], or `Unknown("{")` for a
+    /// hole that isn't closed and `Unknown("}")` for a stray closing brace.
+    pub fn format_string(&mut self) -> TokenKind {
+        debug_assert!(self.prev == '"');
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut error = None;
+
+        loop {
+            match self.bump() {
+                None | Some('\n') => return TokenKind::Unknown("\"".into()),
+                Some('"') => break,
+                Some(c @ ('{' | '}')) if self.first() == c => {
+                    self.bump();
+                    text.push(c);
+                }
+                Some('{') => {
+                    if !text.is_empty() {
+                        parts.push(FormatPart::Text(std::mem::take(&mut text)));
+                    }
+                    match self.hole() {
+                        Some(hole) => parts.push(hole),
+                        None => return TokenKind::Unknown("{".into()),
+                    }
+                }
+                Some('}') => {
+                    error.get_or_insert(TokenKind::Unknown("}".into()));
+                }
+                Some('\\') => {
+                    let escaped = self.bump();
+                    match escaped.and_then(|c| self.escape(c)) {
+                        Some(c) => text.push(c),
+                        None => {
+                            let c = escaped.unwrap_or_default();
+                            error.get_or_insert(TokenKind::Unknown(format!("\\{}", c)));
+                        }
+                    }
+                }
+                Some(c) => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(FormatPart::Text(text));
+        }
+        error.unwrap_or(TokenKind::Format(parts))
+    }
+
+    /// The source of a hole up to its closing `}`, which may itself contain braces and
+    /// strings. `None` if the line ends first.
+    fn hole(&mut self) -> Option<FormatPart> {
+        let mut span = Span {
+            start: self.offset,
+            end: self.offset,
+            line: self.line,
+            col: self.col,
+        };
+        let mut s = String::new();
+        let mut depth = 0;
+        let mut in_string = false;
+        loop {
+            let c = self.bump().filter(|c| *c != '\n')?;
+            match c {
+                '\\' if in_string => {
                     s.push(c);
+                    s.push(self.bump().filter(|c| *c != '\n')?);
+                    continue;
                 }
+                '"' => in_string = !in_string,
+                '{' if !in_string => depth += 1,
+                '}' if !in_string && depth == 0 => break,
+                '}' if !in_string => depth -= 1,
+                _ => {}
             }
+            s.push(c);
         }
-        s
+        span.end = span.start + s.len();
+        Some(FormatPart::Hole(s, span))
     }
 }
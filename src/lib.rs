@@ -0,0 +1,5 @@
+pub mod ast;
+pub mod diagnostic;
+pub mod lexer;
+pub mod repl;
+pub mod xurvm;
@@ -1,38 +1,36 @@
-mod ast;
-mod lexer;
-mod xurvm;
+use std::io::{self, IsTerminal};
 
-use crate::ast::parser::Parser;
-use crate::lexer::{Token, TokenKind};
-use lexer::Tokenizer;
-use xurvm::XurVM;
+use tracing_subscriber::EnvFilter;
+use xur::diagnostic::Renderer;
+use xur::repl::Repl;
 
 fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt::init();
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "warn".into()))
+        .with_ansi(io::stderr().is_terminal())
+        .with_writer(io::stderr)
+        .init();
 
-    let parser = {
-        let t: Vec<Token> = Tokenizer::new(
-            r#"
-            "to_int" % ("ff", 16)
-        "#,
-        )
-        .filter(|t| !matches!(t.kind, TokenKind::Whitespace))
-        .collect();
-
-        for token in &t {
-            println!("{:?}", token);
-        }
-        Parser::new(t)
+    let renderer = if io::stderr().is_terminal() {
+        Renderer::colored()
+    } else {
+        Renderer::plain()
     };
-
-    let mut vm = xurvm::XurVM::new();
-
-    for statement in parser {
-        let statement = statement?;
-        println!("stmnt {:#?}", &statement);
-        let res = vm.execute(statement)?;
-        println!("{:#?}", &res);
+    let mut repl = Repl::new()
+        .with_renderer(renderer)
+        .with_prompt(io::stdin().is_terminal());
+    match std::env::args().nth(1) {
+        Some(path) => {
+            let source = std::fs::read_to_string(&path)?;
+            if let Err(e) = repl.run_script(&path, &source, io::stdout()) {
+                eprint!("{}", repl.report(&e));
+                std::process::exit(1);
+            }
+        }
+        None => {
+            let stdin = io::stdin();
+            repl.run(stdin.lock(), io::stdout(), io::stderr())?;
+        }
     }
-
     Ok(())
 }
@@ -0,0 +1,214 @@
+use std::io::{self, BufRead, Write};
+
+use crate::ast::parser::Parser;
+use crate::ast::Statement;
+use crate::diagnostic::{Diagnostic, Renderer, Source};
+use crate::lexer::{Span, TokenKind, Tokenizer};
+use crate::xurvm::{Value, XurVM};
+
+const PROMPT: &str = "> ";
+const CONTINUATION_PROMPT: &str = "... ";
+const REPL_SOURCE_NAME: &str = "<repl>";
+
+/// Text that has been run, with where its spans start.
+struct Input {
+    name: String,
+    text: String,
+    offset: usize,
+}
+
+pub struct Repl {
+    vm: XurVM,
+    renderer: Renderer,
+    /// Whether [`Repl::run`] writes prompts, for a person typing at a terminal.
+    prompt: bool,
+    /// Everything run so far. Each input gets its own range of span offsets, so an error
+    /// in a function can be shown in the input that defined it.
+    inputs: Vec<Input>,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Self {
+            vm: XurVM::new(),
+            renderer: Renderer::plain(),
+            prompt: false,
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn with_renderer(mut self, renderer: Renderer) -> Self {
+        self.renderer = renderer;
+        self
+    }
+
+    pub fn with_prompt(mut self, prompt: bool) -> Self {
+        self.prompt = prompt;
+        self
+    }
+
+    pub fn parse(source: &str) -> Parser {
+        Parser::new(Tokenizer::new(source).collect())
+    }
+
+    /// Runs every statement in `source` against the vm, returning the value of the last one.
+    /// Bindings made by earlier statements are kept even if a later one fails.
+    pub fn eval(&mut self, source: &str) -> anyhow::Result<Value> {
+        let offset = self.add_input(REPL_SOURCE_NAME, source);
+        let mut last = Value::None;
+        for statement in self.parse_at(source, offset) {
+            last = self.execute(source, offset, statement)?;
+        }
+        Ok(last)
+    }
+
+    /// Keeps `source` for error reports, returning the offset its spans start at. The
+    /// offsets of two inputs never touch, so every span belongs to exactly one of them.
+    fn add_input(&mut self, name: &str, source: &str) -> usize {
+        let offset = self
+            .inputs
+            .last()
+            .map_or(0, |i| i.offset + i.text.len() + 1);
+        self.inputs.push(Input {
+            name: name.to_string(),
+            text: source.to_string(),
+            offset,
+        });
+        offset
+    }
+
+    fn parse_at(&self, source: &str, offset: usize) -> Parser {
+        let start = Span {
+            start: offset,
+            end: offset,
+            line: 1,
+            col: 1,
+        };
+        Parser::new(Tokenizer::at(source, start).collect()).with_precedence(self.vm.precedence())
+    }
+
+    /// Runs one parsed statement, making sure any error it produces points at the source.
+    fn execute(
+        &mut self,
+        source: &str,
+        offset: usize,
+        statement: anyhow::Result<Statement>,
+    ) -> anyhow::Result<Value> {
+        let whole_source = Span {
+            start: offset,
+            end: offset + source.len(),
+            line: 1,
+            col: 1,
+        };
+        let statement = statement.map_err(|e| Diagnostic::attach(e, whole_source))?;
+        let span = statement.span();
+        self.vm
+            .execute(statement)
+            .map_err(|e| Diagnostic::attach(e, span))
+    }
+
+    /// Renders an error returned by [`Repl::eval`] or [`Repl::run_script`], showing
+    /// whichever input it points into.
+    pub fn report(&self, err: &anyhow::Error) -> String {
+        let diagnostic = match Diagnostic::from_error(err) {
+            Some(diagnostic) => diagnostic,
+            None => return format!("error: {:#}\n", err),
+        };
+        let primary = diagnostic.primary.span;
+        let source = self
+            .inputs
+            .iter()
+            .map(|i| Source::new(&i.name, &i.text).with_offset(i.offset))
+            .find(|s| s.contains(primary))
+            .unwrap_or_else(|| Source::new(REPL_SOURCE_NAME, ""));
+        self.renderer.render(&diagnostic, &source)
+    }
+
+    /// Runs a whole script, printing the value of each statement to `out`. Errors
+    /// point into `name`. Unlike [`Repl::run`], the first error ends the script.
+    pub fn run_script(
+        &mut self,
+        name: &str,
+        source: &str,
+        mut out: impl Write,
+    ) -> anyhow::Result<()> {
+        let offset = self.add_input(name, source);
+        for statement in self.parse_at(source, offset) {
+            match self.execute(source, offset, statement)? {
+                Value::None => {}
+                v => writeln!(out, "{}", v)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// True if `source` has brackets, a block comment or a multiline string that are
+    /// still open, meaning the statement carries on onto the next line.
+    pub fn is_incomplete(source: &str) -> bool {
+        let depth: isize = Tokenizer::new(source)
+            .map(|t| match t.kind {
+                TokenKind::OpenParen | TokenKind::OpenBracket | TokenKind::OpenBrace => 1,
+                TokenKind::CloseParen | TokenKind::CloseBracket | TokenKind::CloseBrace => -1,
+                // An unterminated block comment or multiline string.
+                TokenKind::Unknown(s) if s == "#[" || s == "\"\"\"" => 1,
+                _ => 0,
+            })
+            .sum();
+        depth > 0
+    }
+
+    /// Reads lines from `input` until eof, printing each result to `out`
+    /// and each error to `err`, along with prompts if they are turned on.
+    /// Errors never end the session.
+    pub fn run(
+        &mut self,
+        input: impl BufRead,
+        mut out: impl Write,
+        mut err: impl Write,
+    ) -> io::Result<()> {
+        let mut lines = input.lines();
+        let mut buffer = String::new();
+        loop {
+            if self.prompt {
+                let prompt = if buffer.is_empty() {
+                    PROMPT
+                } else {
+                    CONTINUATION_PROMPT
+                };
+                write!(err, "{}", prompt)?;
+                err.flush()?;
+            }
+            let line = match lines.next() {
+                Some(line) => line?,
+                None if buffer.is_empty() => break,
+                // Let the parser report whatever was left open.
+                None => String::new(),
+            };
+            if buffer.is_empty() && line.trim().is_empty() {
+                continue;
+            }
+
+            if !buffer.is_empty() {
+                buffer.push('\n');
+            }
+            buffer.push_str(&line);
+            if !line.is_empty() && Self::is_incomplete(&buffer) {
+                continue;
+            }
+            let line = std::mem::take(&mut buffer);
+
+            match self.eval(&line) {
+                Ok(Value::None) => {}
+                Ok(v) => writeln!(out, "{}", v)?,
+                Err(e) => write!(err, "{}", self.report(&e))?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use std::rc::Rc;
+
+use anyhow::{bail, Context};
+
+use crate::xurvm::var::Value;
+use crate::xurvm::XurVM;
+
+pub(super) fn register(vm: &mut XurVM) {
+    // to_bytes([104, 105]) and to_bytes("hi") are both b"hi"
+    vm.add_fn("to_bytes", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let bytes = match args.pop().context("Failed to pop arg1")? {
+            Value::Bytes(b) => b,
+            Value::String(s) => Rc::new(s.as_bytes().to_vec()),
+            v => Rc::new(
+                v.get_items()?
+                    .into_iter()
+                    .map(|n| {
+                        let n = n.get_int()?;
+                        u8::try_from(n).with_context(|| format!("{} is not a byte", n))
+                    })
+                    .collect::<anyhow::Result<Vec<u8>>>()?,
+            ),
+        };
+        Ok(Value::Bytes(bytes))
+    });
+    // The utf-8 bytes of a string.
+    vm.add_fn("encode", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let s = args.pop().context("Failed to pop arg1")?.get_string()?;
+        Ok(Value::Bytes(Rc::new(s.as_bytes().to_vec())))
+    });
+    vm.add_fn("decode", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let bytes = args.pop().context("Failed to pop arg1")?.get_bytes()?;
+        match std::str::from_utf8(&bytes) {
+            Ok(s) => Ok(Value::String(Rc::new(s.to_string()))),
+            Err(e) => bail!("Bytes are not valid utf-8: {}", e),
+        }
+    });
+    // b"\x01\xff".to_hex is "01ff"
+    vm.add_fn("to_hex", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let bytes = args.pop().context("Failed to pop arg1")?.get_bytes()?;
+        let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        Ok(Value::String(Rc::new(hex)))
+    });
+    vm.add_fn("from_hex", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let hex = args.pop().context("Failed to pop arg1")?.get_string()?;
+        if hex.len() % 2 != 0 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("\"{}\" is not an even number of hex digits", hex);
+        }
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()?;
+        Ok(Value::Bytes(Rc::new(bytes)))
+    });
+}
@@ -0,0 +1,273 @@
+use std::rc::Rc;
+
+use anyhow::{bail, Context};
+
+use crate::xurvm::var::{Callable, Enviroment, Value};
+use crate::xurvm::{Map, Number, Set, XurVM};
+
+/// The longest collection `repeat` will build. Every item is a whole `Value`, so sixteen
+/// million of them is already a gigabyte or so.
+const MAX_REPEAT_LEN: usize = 1 << 24;
+
+fn call(env: &mut dyn Enviroment, f: &Callable, args: Vec<Value>) -> anyhow::Result<Value> {
+    f.clone().call_values(env, args)
+}
+
+/// Rebuilds `items`, as taken from `like.get_items()`, into the kind of collection `like` is.
+fn collect_like(like: &Value, items: Vec<Value>) -> anyhow::Result<Value> {
+    Ok(match like {
+        Value::String(_) => {
+            let s = items
+                .into_iter()
+                .map(|v| v.get_string().map(|s| s.to_string()))
+                .collect::<anyhow::Result<String>>()?;
+            Value::String(Rc::new(s))
+        }
+        Value::Bytes(_) => Value::Bytes(Rc::new(
+            items
+                .into_iter()
+                .map(|v| {
+                    let n = v.get_int()?;
+                    u8::try_from(n).with_context(|| format!("{} is not a byte", n))
+                })
+                .collect::<anyhow::Result<Vec<u8>>>()?,
+        )),
+        Value::Tuple(_) => Value::Tuple(items),
+        Value::Set(_) => Value::Set(Set::from_items(items)?),
+        Value::Map(_) => {
+            let mut map = Map::new();
+            for pair in items {
+                let mut pair = pair.get_array()?.into_iter();
+                if let (Some(k), Some(v)) = (pair.next(), pair.next()) {
+                    map.insert(k, v)?;
+                }
+            }
+            Value::Map(map)
+        }
+        _ => Value::Array(items),
+    })
+}
+
+pub(super) fn register(vm: &mut XurVM) {
+    // ["AA", "BB"].map(to_int(,16))
+    vm.add_fn("map", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let f = args.pop().context("Failed to pop arg1")?.get_callable()?;
+        let items = args.pop().context("Failed to pop arg2")?.get_items()?;
+        let mapped = items
+            .into_iter()
+            .map(|v| call(env, &f, vec![v]))
+            .collect::<anyhow::Result<Vec<Value>>>()?;
+        Ok(Value::Array(mapped))
+    });
+    // Filtering gives back the same kind of collection, so filtering a string gives a
+    // string and filtering a map's `[key, value]` pairs gives a map.
+    vm.add_fn("filter", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let f = args.pop().context("Failed to pop arg1")?.get_callable()?;
+        let collection = args.pop().context("Failed to pop arg2")?;
+        let like = collection.clone();
+
+        let mut kept = Vec::new();
+        for v in collection.get_items()? {
+            if call(env, &f, vec![v.clone()])?.is_truthy() {
+                kept.push(v);
+            }
+        }
+        collect_like(&like, kept)
+    });
+    vm.add_fn("reduce", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let f = args.pop().context("Failed to pop arg1")?.get_callable()?;
+        let mut items = args
+            .pop()
+            .context("Failed to pop arg2")?
+            .get_items()?
+            .into_iter();
+        let first = match items.next() {
+            Some(v) => v,
+            None => bail!("Cannot reduce an empty collection"),
+        };
+        items.try_fold(first, |acc, v| call(env, &f, vec![acc, v]))
+    });
+    vm.add_fn("fold", 3, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let f = args.pop().context("Failed to pop arg1")?.get_callable()?;
+        let init = args.pop().context("Failed to pop arg2")?;
+        let items = args.pop().context("Failed to pop arg3")?.get_items()?;
+        items
+            .into_iter()
+            .try_fold(init, |acc, v| call(env, &f, vec![acc, v]))
+    });
+    // Arrays returned by the function are flattened into the result.
+    vm.add_fn("flat_map", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let f = args.pop().context("Failed to pop arg1")?.get_callable()?;
+        let items = args.pop().context("Failed to pop arg2")?.get_items()?;
+        let mut mapped = Vec::new();
+        for v in items {
+            match call(env, &f, vec![v])? {
+                Value::Array(mut a) => mapped.append(&mut a),
+                x => mapped.push(x),
+            }
+        }
+        Ok(Value::Array(mapped))
+    });
+    // Stops at the end of the shorter collection.
+    vm.add_fn("zip", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let right = args.pop().context("Failed to pop arg1")?.get_items()?;
+        let left = args.pop().context("Failed to pop arg2")?.get_items()?;
+        let pairs = left
+            .into_iter()
+            .zip(right)
+            .map(|(l, r)| Value::Array(vec![l, r]))
+            .collect();
+        Ok(Value::Array(pairs))
+    });
+    vm.add_fn("enumerate", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let items = args.pop().context("Failed to pop arg1")?.get_items()?;
+        let pairs = items
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| Value::Array(vec![Value::Number(i.into()), v]))
+            .collect();
+        Ok(Value::Array(pairs))
+    });
+    vm.add_fn("any", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let f = args.pop().context("Failed to pop arg1")?.get_callable()?;
+        let items = args.pop().context("Failed to pop arg2")?.get_items()?;
+        for v in items {
+            if call(env, &f, vec![v])?.is_truthy() {
+                return Ok(Value::Bool(true));
+            }
+        }
+        Ok(Value::Bool(false))
+    });
+    vm.add_fn("all", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let f = args.pop().context("Failed to pop arg1")?.get_callable()?;
+        let items = args.pop().context("Failed to pop arg2")?.get_items()?;
+        for v in items {
+            if !call(env, &f, vec![v])?.is_truthy() {
+                return Ok(Value::Bool(false));
+            }
+        }
+        Ok(Value::Bool(true))
+    });
+    vm.add_fn("sum", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let items = args.pop().context("Failed to pop arg1")?.get_items()?;
+        let mut total = Number::Int(0);
+        for v in items {
+            total = total.add(&v.get_number()?);
+        }
+        Ok(Value::Number(total))
+    });
+    // The number of items the function is true for.
+    vm.add_fn("count", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let f = args.pop().context("Failed to pop arg1")?.get_callable()?;
+        let items = args.pop().context("Failed to pop arg2")?.get_items()?;
+        let mut n: usize = 0;
+        for v in items {
+            if call(env, &f, vec![v])?.is_truthy() {
+                n += 1;
+            }
+        }
+        Ok(Value::Number(n.into()))
+    });
+    vm.add_fn("len", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let n = match args.pop().context("Failed to pop arg1")? {
+            Value::String(s) => s.chars().count(),
+            Value::Bytes(b) => b.len(),
+            Value::Array(a) | Value::Tuple(a) => a.len(),
+            Value::Set(s) => s.len(),
+            Value::Map(m) => m.len(),
+            x => bail!("{} has no length", x),
+        };
+        Ok(Value::Number(n.into()))
+    });
+    // Whether `item` is in the collection, is a key of a map, or is part of a string.
+    vm.add_fn("contains", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let item = args.pop().context("Failed to pop arg1")?;
+        let found = match args.pop().context("Failed to pop arg2")? {
+            Value::String(s) => s.contains(item.get_string()?.as_str()),
+            Value::Set(s) => s.contains(&item),
+            Value::Map(m) => m.get(&item).is_some(),
+            c => c.get_items()?.contains(&item),
+        };
+        Ok(Value::Bool(found))
+    });
+    vm.add_fn("to_array", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let items = args.pop().context("Failed to pop arg1")?.get_items()?;
+        Ok(Value::Array(items))
+    });
+    vm.add_fn("to_tuple", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let items = args.pop().context("Failed to pop arg1")?.get_items()?;
+        Ok(Value::Tuple(items))
+    });
+    // The index of the first `item` in the collection, or of the first place a string
+    // contains it. `none` if it isn't there.
+    vm.add_fn("find", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let item = args.pop().context("Failed to pop arg1")?;
+        let index = match args.pop().context("Failed to pop arg2")? {
+            Value::String(s) => {
+                let sub = item.get_string()?;
+                s.find(sub.as_str()).map(|i| s[..i].chars().count())
+            }
+            c => c.get_items()?.iter().position(|v| *v == item),
+        };
+        Ok(index.map_or(Value::None, |i| Value::Number(i.into())))
+    });
+    // "hello".slice(1, 3) is "el". Negative indexes count from the end, and an end of
+    // `none` slices to the end.
+    vm.add_fn("slice", 3, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let end = args.pop().context("Failed to pop arg1")?;
+        let start = args.pop().context("Failed to pop arg2")?.get_int()?;
+        let c = args.pop().context("Failed to pop arg3")?;
+        let mut items = c.clone().get_items()?;
+        let len = items.len() as i64;
+        let index = |i: i64| (if i < 0 { len + i } else { i }).clamp(0, len) as usize;
+        let end = match end {
+            Value::None => items.len(),
+            n => index(n.get_int()?),
+        };
+        let start = index(start).min(end);
+        items.truncate(end);
+        collect_like(&c, items.split_off(start))
+    });
+    // "ab".repeat(3) is "ababab"
+    vm.add_fn("repeat", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let n = args.pop().context("Failed to pop arg1")?.get_usize()?;
+        let c = args.pop().context("Failed to pop arg2")?;
+        let items = c.clone().get_items()?;
+        let len = match items.len().checked_mul(n) {
+            Some(len) if len <= MAX_REPEAT_LEN => len,
+            _ => bail!(
+                "Repeating {} items {} times is too many, the most is {}",
+                items.len(),
+                n,
+                MAX_REPEAT_LEN
+            ),
+        };
+        let repeated = items.iter().cloned().cycle().take(len).collect();
+        collect_like(&c, repeated)
+    });
+    vm.add_fn("reverse", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let c = args.pop().context("Failed to pop arg1")?;
+        let mut items = c.clone().get_items()?;
+        items.reverse();
+        collect_like(&c, items)
+    });
+}
@@ -0,0 +1,176 @@
+use std::rc::Rc;
+
+use anyhow::{bail, Context};
+
+use crate::xurvm::var::Value;
+use crate::xurvm::{Map, Number, XurVM};
+
+const OPTIONS: &str = r#""header", "delimiter", "quote", "escape" and "numbers""#;
+
+/// The options map both `csv_parse` and `csv_dump` take.
+#[derive(Default)]
+struct CsvOptions {
+    /// `None` to guess from the first row.
+    header: Option<bool>,
+    /// `None` to guess from the first line.
+    delimiter: Option<u8>,
+    quote: Option<u8>,
+    /// Quotes are escaped by doubling them unless this is set.
+    escape: Option<u8>,
+    /// Read fields that look like numbers as numbers.
+    numbers: bool,
+}
+
+/// A single ascii character, such as a delimiter.
+fn byte(name: &str, v: Value) -> anyhow::Result<u8> {
+    let s = v.get_string()?;
+    match s.as_bytes() {
+        [b] if b.is_ascii() => Ok(*b),
+        _ => bail!("The {} must be one ascii character, got {:?}", name, s),
+    }
+}
+
+impl CsvOptions {
+    /// Reads the arguments after the first, which can be an options map or `none`.
+    fn from_args(mut rest: Vec<Value>) -> anyhow::Result<CsvOptions> {
+        if rest.len() > 1 {
+            bail!("Expected at most 2 arguments, got {}", rest.len() + 1);
+        }
+        let mut options = CsvOptions::default();
+        let map = match rest.pop() {
+            None | Some(Value::None) => return Ok(options),
+            Some(map) => map,
+        };
+        for (k, v) in map.get_map()? {
+            match k.get_string()?.as_str() {
+                "header" => options.header = Some(v.get_bool()?),
+                "delimiter" => options.delimiter = Some(byte("delimiter", v)?),
+                "quote" => options.quote = Some(byte("quote", v)?),
+                "escape" => options.escape = Some(byte("escape", v)?),
+                "numbers" => options.numbers = v.get_bool()?,
+                k => bail!("Unknown csv option {:?}, expected one of {}", k, OPTIONS),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// A header has a distinct, non-empty name for every column, none of them numbers.
+fn looks_like_header(row: &csv::StringRecord) -> bool {
+    row.iter().enumerate().all(|(i, field)| {
+        !field.trim().is_empty()
+            && Number::parse(field).is_err()
+            && !row.iter().take(i).any(|f| f == field)
+    })
+}
+
+pub(super) fn register(vm: &mut XurVM) {
+    // text.csv_parse, or csv_parse(text, {"delimiter": "\t", "header": false})
+    vm.add_variadic_fn("csv_parse", 1, |env, args| {
+        let mut args = env.resolve_args(args)?.into_iter();
+        let text = args.next().context("Failed to pop arg1")?.get_string()?;
+        let options = CsvOptions::from_args(args.collect())?;
+
+        let first_line = text.lines().next().unwrap_or_default();
+        let delimiter = options.delimiter.unwrap_or(
+            if first_line.contains('\t') && !first_line.contains(',') {
+                b'\t'
+            } else {
+                b','
+            },
+        );
+        let mut reader = csv::ReaderBuilder::new();
+        reader
+            .has_headers(false)
+            .delimiter(delimiter)
+            .quote(options.quote.unwrap_or(b'"'));
+        if options.escape.is_some() {
+            reader.escape(options.escape).double_quote(false);
+        }
+
+        let field = |s: &str| match Number::parse(s) {
+            Ok(n) if options.numbers && !s.trim().is_empty() => Value::Number(n),
+            _ => Value::String(Rc::new(s.to_string())),
+        };
+        let mut records = reader.from_reader(text.as_bytes()).into_records();
+        let header = match records.next().transpose().context("Invalid CSV")? {
+            Some(first) if options.header.unwrap_or_else(|| looks_like_header(&first)) => {
+                Some(first)
+            }
+            Some(first) => {
+                let row = Value::Array(first.iter().map(field).collect());
+                return std::iter::once(Ok(row))
+                    .chain(records.map(|r| {
+                        let r = r.context("Invalid CSV")?;
+                        Ok(Value::Array(r.iter().map(field).collect()))
+                    }))
+                    .collect::<anyhow::Result<Vec<Value>>>()
+                    .map(Value::Array);
+            }
+            None => None,
+        };
+
+        let mut rows = Vec::new();
+        if let Some(header) = header {
+            for record in records {
+                let record = record.context("Invalid CSV")?;
+                let mut row = Map::new();
+                for (name, value) in header.iter().zip(record.iter()) {
+                    row.insert(Value::String(Rc::new(name.to_string())), field(value))?;
+                }
+                rows.push(Value::Map(row));
+            }
+        }
+        Ok(Value::Array(rows))
+    });
+    // Rows of maps are written under a header of their keys, rows of arrays as they are.
+    vm.add_variadic_fn("csv_dump", 1, |env, args| {
+        let mut args = env.resolve_args(args)?.into_iter();
+        let rows = args.next().context("Failed to pop arg1")?.get_items()?;
+        let options = CsvOptions::from_args(args.collect())?;
+        if options.numbers {
+            bail!("\"numbers\" is only an option for csv_parse");
+        }
+
+        let mut writer = csv::WriterBuilder::new();
+        writer
+            .flexible(true)
+            .delimiter(options.delimiter.unwrap_or(b','))
+            .quote(options.quote.unwrap_or(b'"'));
+        if let Some(escape) = options.escape {
+            writer.escape(escape).double_quote(false);
+        }
+        let mut writer = writer.from_writer(Vec::new());
+
+        // Every key of every map, in the order they are first seen.
+        let mut columns: Vec<Value> = Vec::new();
+        for row in rows.iter() {
+            if let Value::Map(m) = row {
+                for (k, _) in m.iter() {
+                    if !columns.contains(k) {
+                        columns.push(k.clone());
+                    }
+                }
+            }
+        }
+        let text = |v: &Value| match v {
+            Value::None => String::new(),
+            v => v.display().to_string(),
+        };
+        if !columns.is_empty() && options.header.unwrap_or(true) {
+            writer.write_record(columns.iter().map(text))?;
+        }
+        for row in rows {
+            let fields: Vec<String> = match row {
+                Value::Map(m) => columns
+                    .iter()
+                    .map(|k| m.get(k).map(text).unwrap_or_default())
+                    .collect(),
+                row => row.get_items()?.iter().map(text).collect(),
+            };
+            writer.write_record(&fields)?;
+        }
+        let bytes = writer.into_inner().context("Failed to write csv")?;
+        Ok(Value::String(Rc::new(String::from_utf8(bytes)?)))
+    });
+}
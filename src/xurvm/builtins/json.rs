@@ -0,0 +1,151 @@
+use std::rc::Rc;
+
+use anyhow::{bail, Context};
+use num_traits::ToPrimitive;
+use serde::Serialize;
+use serde_json::Value as Json;
+
+use crate::xurvm::var::Value;
+use crate::xurvm::{Map, Number, XurVM};
+
+/// A path into a JSON document, such as `$.users[0].name`.
+#[derive(Clone, Default)]
+struct JsonPath(String);
+
+impl JsonPath {
+    fn index(&self, i: usize) -> JsonPath {
+        JsonPath(format!("{}[{}]", self.0, i))
+    }
+
+    fn key(&self, key: &str) -> JsonPath {
+        let plain = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if plain {
+            JsonPath(format!("{}.{}", self.0, key))
+        } else {
+            JsonPath(format!("{}[{:?}]", self.0, key))
+        }
+    }
+}
+
+impl std::fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "${}", self.0)
+    }
+}
+
+fn from_json(json: Json) -> Value {
+    match json {
+        Json::Null => Value::None,
+        Json::Bool(b) => Value::Bool(b),
+        Json::Number(n) => Value::Number(match (n.as_i64(), n.as_u64()) {
+            (Some(n), _) => Number::from(n),
+            (None, Some(n)) => Number::from(num_bigint::BigInt::from(n)),
+            _ => Number::from(n.as_f64().unwrap_or(f64::NAN)),
+        }),
+        Json::String(s) => Value::String(Rc::new(s)),
+        Json::Array(a) => Value::Array(a.into_iter().map(from_json).collect()),
+        Json::Object(o) => {
+            let mut map = Map::new();
+            for (k, v) in o {
+                map.insert(Value::String(Rc::new(k)), from_json(v))
+                    .expect("strings can be keys");
+            }
+            Value::Map(map)
+        }
+    }
+}
+
+fn to_json(value: &Value, path: &JsonPath) -> anyhow::Result<Json> {
+    Ok(match value {
+        Value::None => Json::Null,
+        Value::Bool(b) => Json::Bool(*b),
+        Value::Number(Number::Int(n)) => Json::from(*n),
+        Value::Number(Number::Big(n)) => match n.to_u64() {
+            Some(n) => Json::from(n),
+            None => bail!("{} is too large for JSON at {}", n, path),
+        },
+        Value::Number(Number::Float(n)) => match serde_json::Number::from_f64(*n) {
+            Some(n) => Json::Number(n),
+            None => bail!("{:?} can't be written as JSON at {}", n, path),
+        },
+        Value::String(s) => Json::String(s.to_string()),
+        Value::Array(items) | Value::Tuple(items) => Json::Array(
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, v)| to_json(v, &path.index(i)))
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        Value::Set(s) => Json::Array(
+            s.iter()
+                .enumerate()
+                .map(|(i, v)| to_json(v, &path.index(i)))
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        Value::Map(m) => {
+            let mut object = serde_json::Map::new();
+            for (k, v) in m.iter() {
+                // Like other JSON writers, numbers and bools become string keys.
+                let key = match k {
+                    Value::String(s) => s.to_string(),
+                    Value::Number(_) | Value::Bool(_) => k.to_string(),
+                    k => bail!("{} {} can't be a JSON key at {}", k.type_name(), k, path),
+                };
+                let value = to_json(v, &path.key(&key))?;
+                object.insert(key, value);
+            }
+            Json::Object(object)
+        }
+        v => bail!(
+            "{} {} can't be written as JSON at {}",
+            v.type_name(),
+            v,
+            path
+        ),
+    })
+}
+
+pub(super) fn register(vm: &mut XurVM) {
+    vm.add_fn("json_parse", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let s = args.pop().context("Failed to pop arg1")?.get_string()?;
+        let mut de = serde_json::Deserializer::from_str(&s);
+        let json: Json = match serde_path_to_error::deserialize(&mut de) {
+            Ok(json) => json,
+            Err(e) => {
+                let mut path = JsonPath::default();
+                for segment in e.path().iter() {
+                    path = match segment {
+                        serde_path_to_error::Segment::Seq { index } => path.index(*index),
+                        serde_path_to_error::Segment::Map { key } => path.key(key),
+                        _ => path,
+                    };
+                }
+                bail!("Invalid JSON at {}: {}", path, e.inner());
+            }
+        };
+        if let Err(e) = de.end() {
+            bail!("Invalid JSON: {}", e);
+        }
+        Ok(from_json(json))
+    });
+    // An indent of 0 writes everything on one line.
+    vm.add_fn("json_dump", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let indent = args.pop().context("Failed to pop arg1")?.get_usize()?;
+        let value = args.pop().context("Failed to pop arg2")?;
+        let json = to_json(&value, &JsonPath::default())?;
+        let s = if indent == 0 {
+            json.to_string()
+        } else {
+            let indent = " ".repeat(indent);
+            let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+            let mut out = Vec::new();
+            let mut ser = serde_json::Serializer::with_formatter(&mut out, formatter);
+            json.serialize(&mut ser)?;
+            String::from_utf8(out).expect("JSON is utf-8")
+        };
+        Ok(Value::String(Rc::new(s)))
+    });
+}
@@ -0,0 +1,49 @@
+use anyhow::Context;
+
+use crate::xurvm::var::Value;
+use crate::xurvm::XurVM;
+
+pub(super) fn register(vm: &mut XurVM) {
+    vm.add_fn("keys", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let map = args.pop().context("Failed to pop arg1")?.get_map()?;
+        Ok(Value::Array(map.into_iter().map(|(k, _)| k).collect()))
+    });
+    vm.add_fn("values", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let map = args.pop().context("Failed to pop arg1")?.get_map()?;
+        Ok(Value::Array(map.into_iter().map(|(_, v)| v).collect()))
+    });
+    // {"a": 1}.items is [["a", 1]]
+    vm.add_fn("items", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let map = args.pop().context("Failed to pop arg1")?.get_map()?;
+        Ok(Value::Array(Value::Map(map).get_items()?))
+    });
+    // A missing key gives `none`.
+    vm.add_fn("get", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let key = args.pop().context("Failed to pop arg1")?;
+        let map = args.pop().context("Failed to pop arg2")?.get_map()?;
+        Ok(map.get(&key).cloned().unwrap_or(Value::None))
+    });
+    // Gives back a new map, the original is left as it was.
+    vm.add_fn("set", 3, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let value = args.pop().context("Failed to pop arg1")?;
+        let key = args.pop().context("Failed to pop arg2")?;
+        let mut map = args.pop().context("Failed to pop arg3")?.get_map()?;
+        map.insert(key, value)?;
+        Ok(Value::Map(map))
+    });
+    // Keys in both take the value from the right.
+    vm.add_fn("merge", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let right = args.pop().context("Failed to pop arg1")?.get_map()?;
+        let mut left = args.pop().context("Failed to pop arg2")?.get_map()?;
+        for (k, v) in right {
+            left.insert(k, v)?;
+        }
+        Ok(Value::Map(left))
+    });
+}
@@ -0,0 +1,26 @@
+mod bytes;
+mod collections;
+mod csv;
+mod json;
+mod maps;
+mod numbers;
+mod operators;
+mod regexes;
+mod sets;
+mod strings;
+
+use crate::xurvm::XurVM;
+
+/// Registers the standard library on top of the core builtins in `vmcore`.
+pub(super) fn register(vm: &mut XurVM) {
+    collections::register(vm);
+    maps::register(vm);
+    numbers::register(vm);
+    operators::register(vm);
+    sets::register(vm);
+    bytes::register(vm);
+    strings::register(vm);
+    regexes::register(vm);
+    json::register(vm);
+    csv::register(vm);
+}
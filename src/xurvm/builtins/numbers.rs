@@ -0,0 +1,106 @@
+use anyhow::{bail, Context};
+
+use crate::ast::value::Expression;
+use crate::xurvm::var::{Enviroment, Value};
+use crate::xurvm::{Number, XurVM};
+
+/// Enough digits to write out the smallest float exactly.
+const MAX_FIXED_DIGITS: usize = 1074;
+
+/// `n` in `radix` after `prefix`, as in `-0xff`, so it reads back with `parse`.
+fn prefixed(
+    env: &mut dyn Enviroment,
+    args: Vec<Expression>,
+    radix: u32,
+    prefix: &str,
+) -> anyhow::Result<Value> {
+    let mut args = env.resolve_args(args)?;
+    let n = args.pop().context("Failed to pop arg1")?.get_number()?;
+    if n.is_float() {
+        bail!("{} is not an integer", n);
+    }
+    let digits = n.to_str_radix(radix)?;
+    Ok(Value::string(match digits.strip_prefix('-') {
+        Some(digits) => format!("-{}{}", prefix, digits),
+        None => format!("{}{}", prefix, digits),
+    }))
+}
+
+/// Puts a `,` between every three digits of the whole part of `s`.
+fn group_thousands(s: &str) -> String {
+    let (sign, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", s),
+    };
+    let split = unsigned.find('.').unwrap_or(unsigned.len());
+    let (whole, fraction) = unsigned.split_at(split);
+
+    let mut grouped = String::new();
+    for (i, c) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    format!("{}{}{}", sign, grouped, fraction)
+}
+
+pub(super) fn register(vm: &mut XurVM) {
+    // The reverse of `to_int`, so to_str(255, 16) is "ff".
+    vm.add_fn("to_str", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let radix = args.pop().context("Failed to pop arg1")?.get_int()?;
+        let n = args.pop().context("Failed to pop arg2")?.get_number()?;
+        let radix = u32::try_from(radix).context("Radix out of range")?;
+        Ok(Value::string(n.to_str_radix(radix)?))
+    });
+    vm.add_fn("hex", 1, |env, args| prefixed(env, args, 16, "0x"));
+    vm.add_fn("bin", 1, |env, args| prefixed(env, args, 2, "0b"));
+    vm.add_fn("oct", 1, |env, args| prefixed(env, args, 8, "0o"));
+    // "42", "-1.5", "0xff" and "0b101" all parse.
+    vm.add_fn("parse", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let s = args.pop().context("Failed to pop arg1")?.get_string()?;
+        Ok(Value::Number(Number::parse(&s)?))
+    });
+    // 7.zero_pad(3) is "007". Strings of digits can be padded too, and a sign stays in front.
+    vm.add_fn("zero_pad", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let width = args.pop().context("Failed to pop arg1")?.get_usize()?;
+        let s = match args.pop().context("Failed to pop arg2")? {
+            Value::Number(n) => n.to_string(),
+            v => v.get_string()?.to_string(),
+        };
+        let (sign, digits) = match s.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", s.as_str()),
+        };
+        let zeros = "0".repeat(width.saturating_sub(sign.len() + digits.chars().count()));
+        Ok(Value::string(format!("{}{}{}", sign, zeros, digits)))
+    });
+    // 1234567.thousands is "1,234,567"
+    vm.add_fn("thousands", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let n = args.pop().context("Failed to pop arg1")?.get_number()?;
+        let s = n.to_string();
+        // Very large or small floats are written with an exponent.
+        if s.contains(['e', 'i', 'N']) {
+            return Ok(Value::string(s));
+        }
+        Ok(Value::string(group_thousands(&s)))
+    });
+    // 3.14159.to_fixed(2) is "3.14"
+    vm.add_fn("to_fixed", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let digits = args.pop().context("Failed to pop arg1")?.get_usize()?;
+        let n = args.pop().context("Failed to pop arg2")?.get_number()?;
+        if digits > MAX_FIXED_DIGITS {
+            bail!(
+                "Can't show {} digits, the most is {}",
+                digits,
+                MAX_FIXED_DIGITS
+            );
+        }
+        Ok(Value::string(format!("{:.*}", digits, n.to_f64())))
+    });
+}
@@ -0,0 +1,121 @@
+use std::cmp::Ordering;
+
+use anyhow::Context;
+
+use crate::ast::value::Expression;
+use crate::xurvm::var::{Enviroment, Value};
+use crate::xurvm::{Number, XurVM};
+
+/// Both arguments of a binary operator, in the order they were written.
+fn operands(env: &mut dyn Enviroment, args: Vec<Expression>) -> anyhow::Result<(Value, Value)> {
+    let mut args = env.resolve_args(args)?;
+    let rhs = args.pop().context("Failed to pop arg1")?;
+    let lhs = args.pop().context("Failed to pop arg2")?;
+    Ok((lhs, rhs))
+}
+
+fn numbers(env: &mut dyn Enviroment, args: Vec<Expression>) -> anyhow::Result<(Number, Number)> {
+    let (lhs, rhs) = operands(env, args)?;
+    Ok((lhs.get_number()?, rhs.get_number()?))
+}
+
+fn compare(env: &mut dyn Enviroment, args: Vec<Expression>) -> anyhow::Result<Ordering> {
+    let (lhs, rhs) = operands(env, args)?;
+    lhs.compare(&rhs)
+}
+
+pub(super) fn register(vm: &mut XurVM) {
+    // Arithmetic. `+` lives with the core builtins.
+    vm.add_fn("-", 2, |env, args| {
+        let (lhs, rhs) = numbers(env, args)?;
+        Ok(Value::Number(lhs.sub(&rhs)))
+    });
+    // -x is neg(x)
+    vm.add_fn("neg", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let n = args.pop().context("Failed to pop arg1")?.get_number()?;
+        Ok(Value::Number(n.neg()))
+    });
+    vm.add_fn("*", 2, |env, args| {
+        let (lhs, rhs) = numbers(env, args)?;
+        Ok(Value::Number(lhs.mul(&rhs)))
+    });
+    // 7 / 2 is 3.5
+    vm.add_fn("/", 2, |env, args| {
+        let (lhs, rhs) = numbers(env, args)?;
+        Ok(Value::Number(lhs.div(&rhs)?))
+    });
+    // 7 // 2 is 3
+    vm.add_fn("//", 2, |env, args| {
+        let (lhs, rhs) = numbers(env, args)?;
+        Ok(Value::Number(lhs.floor_div(&rhs)?))
+    });
+    vm.add_fn("**", 2, |env, args| {
+        let (lhs, rhs) = numbers(env, args)?;
+        Ok(Value::Number(lhs.pow(&rhs)?))
+    });
+    // -7 mod 3 is 2
+    vm.add_fn("mod", 2, |env, args| {
+        let (lhs, rhs) = numbers(env, args)?;
+        Ok(Value::Number(lhs.modulo(&rhs)?))
+    });
+
+    // Comparison
+    vm.add_fn("==", 2, |env, args| {
+        let (lhs, rhs) = operands(env, args)?;
+        Ok(Value::Bool(lhs == rhs))
+    });
+    vm.add_fn("!=", 2, |env, args| {
+        let (lhs, rhs) = operands(env, args)?;
+        Ok(Value::Bool(lhs != rhs))
+    });
+    vm.add_fn("<", 2, |env, args| {
+        Ok(Value::Bool(compare(env, args)?.is_lt()))
+    });
+    vm.add_fn("<=", 2, |env, args| {
+        Ok(Value::Bool(compare(env, args)?.is_le()))
+    });
+    vm.add_fn(">", 2, |env, args| {
+        Ok(Value::Bool(compare(env, args)?.is_gt()))
+    });
+    vm.add_fn(">=", 2, |env, args| {
+        Ok(Value::Bool(compare(env, args)?.is_ge()))
+    });
+
+    // Logic
+    vm.add_fn("&&", 2, |env, args| {
+        let (lhs, rhs) = operands(env, args)?;
+        Ok(Value::Bool(lhs.is_truthy() && rhs.is_truthy()))
+    });
+    vm.add_fn("||", 2, |env, args| {
+        let (lhs, rhs) = operands(env, args)?;
+        Ok(Value::Bool(lhs.is_truthy() || rhs.is_truthy()))
+    });
+    vm.add_fn("!", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let value = args.pop().context("Failed to pop arg1")?;
+        Ok(Value::Bool(!value.is_truthy()))
+    });
+
+    // Bitwise
+    vm.add_fn("&", 2, |env, args| {
+        let (lhs, rhs) = numbers(env, args)?;
+        Ok(Value::Number(lhs.bitand(&rhs)?))
+    });
+    vm.add_fn("|", 2, |env, args| {
+        let (lhs, rhs) = numbers(env, args)?;
+        Ok(Value::Number(lhs.bitor(&rhs)?))
+    });
+    vm.add_fn("^", 2, |env, args| {
+        let (lhs, rhs) = numbers(env, args)?;
+        Ok(Value::Number(lhs.bitxor(&rhs)?))
+    });
+    vm.add_fn("<<", 2, |env, args| {
+        let (lhs, rhs) = numbers(env, args)?;
+        Ok(Value::Number(lhs.shl(&rhs)?))
+    });
+    vm.add_fn(">>", 2, |env, args| {
+        let (lhs, rhs) = numbers(env, args)?;
+        Ok(Value::Number(lhs.shr(&rhs)?))
+    });
+}
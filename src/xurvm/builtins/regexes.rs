@@ -0,0 +1,77 @@
+use std::rc::Rc;
+
+use anyhow::{bail, Context};
+use regex::Regex;
+
+use crate::xurvm::var::{Enviroment, Value};
+use crate::xurvm::{Map, XurVM};
+
+/// A `re("...")` value, or a string to compile as one.
+fn regex(env: &mut dyn Enviroment, v: Value) -> anyhow::Result<Rc<Regex>> {
+    match v {
+        Value::Regex(re) => Ok(re),
+        Value::String(s) => env.regex(&s),
+        x => bail!("{} is not a regex", x),
+    }
+}
+
+pub(super) fn register(vm: &mut XurVM) {
+    vm.add_fn("re", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let pattern = args.pop().context("Failed to pop arg1")?.get_string()?;
+        Ok(Value::Regex(env.regex(&pattern)?))
+    });
+    // lines.filter(matches(,re("\\d+")))
+    vm.add_fn("matches", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let re = regex(env, args.pop().context("Failed to pop arg1")?)?;
+        let s = args.pop().context("Failed to pop arg2")?.get_string()?;
+        Ok(Value::Bool(re.is_match(&s)))
+    });
+    // The groups of the first match, starting with the whole match, or a map of the
+    // named groups if the pattern has any. Groups that didn't take part are `none`.
+    vm.add_fn("captures", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let re = regex(env, args.pop().context("Failed to pop arg1")?)?;
+        let s = args.pop().context("Failed to pop arg2")?.get_string()?;
+        let caps = match re.captures(&s) {
+            Some(caps) => caps,
+            None => return Ok(Value::None),
+        };
+        let group = |m: Option<regex::Match>| m.map_or(Value::None, |m| Value::string(m.as_str()));
+
+        if re.capture_names().any(|name| name.is_some()) {
+            let mut map = Map::new();
+            for name in re.capture_names().flatten() {
+                map.insert(Value::string(name), group(caps.name(name)))?;
+            }
+            Ok(Value::Map(map))
+        } else {
+            Ok(Value::Array(caps.iter().map(group).collect()))
+        }
+    });
+    vm.add_fn("find_all", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let re = regex(env, args.pop().context("Failed to pop arg1")?)?;
+        let s = args.pop().context("Failed to pop arg2")?.get_string()?;
+        Ok(Value::Array(
+            re.find_iter(&s)
+                .map(|m| Value::string(m.as_str()))
+                .collect(),
+        ))
+    });
+    // Replaces every match. `$1` or `${name}` in the replacement is that group.
+    vm.add_fn("re_replace", 3, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let with = args.pop().context("Failed to pop arg1")?.get_string()?;
+        let re = regex(env, args.pop().context("Failed to pop arg2")?)?;
+        let s = args.pop().context("Failed to pop arg3")?.get_string()?;
+        Ok(Value::string(re.replace_all(&s, with.as_str())))
+    });
+    vm.add_fn("re_split", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let re = regex(env, args.pop().context("Failed to pop arg1")?)?;
+        let s = args.pop().context("Failed to pop arg2")?.get_string()?;
+        Ok(Value::Array(re.split(&s).map(Value::string).collect()))
+    });
+}
@@ -0,0 +1,40 @@
+use anyhow::Context;
+
+use crate::xurvm::var::Value;
+use crate::xurvm::{Set, XurVM};
+
+/// Set operations take any collection, so `[1, 2] union [2, 3]` works.
+fn to_set(value: Value) -> anyhow::Result<Set> {
+    match value {
+        Value::Set(s) => Ok(s),
+        v => Set::from_items(v.get_items()?),
+    }
+}
+
+pub(super) fn register(vm: &mut XurVM) {
+    // to_set([1, 2, 2]) is to_set([1, 2])
+    vm.add_fn("to_set", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let items = args.pop().context("Failed to pop arg1")?;
+        Ok(Value::Set(to_set(items)?))
+    });
+    vm.add_fn("union", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let right = to_set(args.pop().context("Failed to pop arg1")?)?;
+        let left = to_set(args.pop().context("Failed to pop arg2")?)?;
+        Ok(Value::Set(left.union(right)))
+    });
+    vm.add_fn("intersect", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let right = to_set(args.pop().context("Failed to pop arg1")?)?;
+        let left = to_set(args.pop().context("Failed to pop arg2")?)?;
+        Ok(Value::Set(left.intersect(&right)))
+    });
+    // Everything in the left that isn't in the right.
+    vm.add_fn("diff", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let right = to_set(args.pop().context("Failed to pop arg1")?)?;
+        let left = to_set(args.pop().context("Failed to pop arg2")?)?;
+        Ok(Value::Set(left.diff(&right)))
+    });
+}
@@ -0,0 +1,95 @@
+use anyhow::{bail, Context};
+
+use crate::ast::value::Expression;
+use crate::xurvm::var::{Enviroment, Value};
+use crate::xurvm::XurVM;
+
+fn strings<'a>(items: impl Iterator<Item = &'a str>) -> Value {
+    Value::Array(items.map(Value::string).collect())
+}
+
+/// Pads `s` with spaces up to `width` characters, on the left or the right.
+fn pad(env: &mut dyn Enviroment, args: Vec<Expression>, left: bool) -> anyhow::Result<Value> {
+    let mut args = env.resolve_args(args)?;
+    let width = args.pop().context("Failed to pop arg1")?.get_usize()?;
+    let s = args.pop().context("Failed to pop arg2")?.get_string()?;
+    let padding = " ".repeat(width.saturating_sub(s.chars().count()));
+    Ok(if left {
+        Value::string(padding + &s)
+    } else {
+        Value::string(s.to_string() + &padding)
+    })
+}
+
+pub(super) fn register(vm: &mut XurVM) {
+    // "a,b".split(",") is ["a", "b"]
+    vm.add_fn("split", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let sep = args.pop().context("Failed to pop arg1")?.get_string()?;
+        let s = args.pop().context("Failed to pop arg2")?.get_string()?;
+        if sep.is_empty() {
+            bail!("Can't split on an empty string, use `chars` instead");
+        }
+        Ok(strings(s.split(sep.as_str())))
+    });
+    // Joins the items with `sep`, writing them as `f"..."` strings would.
+    vm.add_fn("join", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let sep = args.pop().context("Failed to pop arg1")?.get_string()?;
+        let items = args.pop().context("Failed to pop arg2")?.get_items()?;
+        let joined = items
+            .iter()
+            .map(|v| v.display().to_string())
+            .collect::<Vec<String>>()
+            .join(&sep);
+        Ok(Value::string(joined))
+    });
+    vm.add_fn("trim", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let s = args.pop().context("Failed to pop arg1")?.get_string()?;
+        Ok(Value::string(s.trim()))
+    });
+    vm.add_fn("lower", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let s = args.pop().context("Failed to pop arg1")?.get_string()?;
+        Ok(Value::string(s.to_lowercase()))
+    });
+    vm.add_fn("upper", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let s = args.pop().context("Failed to pop arg1")?.get_string()?;
+        Ok(Value::string(s.to_uppercase()))
+    });
+    vm.add_fn("lines", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let s = args.pop().context("Failed to pop arg1")?.get_string()?;
+        Ok(strings(s.lines()))
+    });
+    vm.add_fn("chars", 1, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let s = args.pop().context("Failed to pop arg1")?.get_string()?;
+        Ok(Value::Array(s.chars().map(Value::string).collect()))
+    });
+    // "a-b".replace("-", "+") replaces every match.
+    vm.add_fn("replace", 3, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let to = args.pop().context("Failed to pop arg1")?.get_string()?;
+        let from = args.pop().context("Failed to pop arg2")?.get_string()?;
+        let s = args.pop().context("Failed to pop arg3")?.get_string()?;
+        Ok(Value::string(s.replace(from.as_str(), &to)))
+    });
+    vm.add_fn("starts_with", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let prefix = args.pop().context("Failed to pop arg1")?.get_string()?;
+        let s = args.pop().context("Failed to pop arg2")?.get_string()?;
+        Ok(Value::Bool(s.starts_with(prefix.as_str())))
+    });
+    vm.add_fn("ends_with", 2, |env, args| {
+        let mut args = env.resolve_args(args)?;
+        let suffix = args.pop().context("Failed to pop arg1")?.get_string()?;
+        let s = args.pop().context("Failed to pop arg2")?.get_string()?;
+        Ok(Value::Bool(s.ends_with(suffix.as_str())))
+    });
+    // "7".pad_left(3) is "  7"
+    vm.add_fn("pad_left", 2, |env, args| pad(env, args, true));
+    vm.add_fn("pad_right", 2, |env, args| pad(env, args, false));
+}
@@ -0,0 +1,105 @@
+use crate::ast::{Pattern, PatternKind};
+use crate::diagnostic::Diagnostic;
+use crate::lexer::Span;
+use crate::xurvm::Value;
+
+/// A name bound by a pattern, with the span of the part of the pattern that binds it.
+pub type PatternBinding = (String, Span, Value);
+
+/// Matches `value` against `pattern`, returning what each name in it should be bound
+/// to. Nothing is bound unless the whole pattern matches.
+pub fn destructure(pattern: &Pattern, value: Value) -> anyhow::Result<Vec<PatternBinding>> {
+    let mut bindings = Vec::new();
+    destructure_into(pattern, value, &mut bindings)?;
+    Ok(bindings)
+}
+
+fn mismatch(pattern: &Pattern, message: String) -> anyhow::Error {
+    Diagnostic::error(message, pattern.span)
+        .with_primary_label("pattern doesn't match")
+        .into()
+}
+
+fn destructure_into(
+    pattern: &Pattern,
+    value: Value,
+    bindings: &mut Vec<PatternBinding>,
+) -> anyhow::Result<()> {
+    match &pattern.kind {
+        PatternKind::Wildcard => {}
+        PatternKind::Bind(name) => bindings.push((name.clone(), pattern.span, value)),
+        PatternKind::Literal(literal) => {
+            let expected = Value::from(literal.clone());
+            if expected != value {
+                return Err(mismatch(
+                    pattern,
+                    format!("Expected {}, got {}", expected, value),
+                ));
+            }
+        }
+        PatternKind::Array { items, rest } => {
+            let mut elements = match value {
+                Value::Array(a) | Value::Tuple(a) => a,
+                v => {
+                    return Err(mismatch(
+                        pattern,
+                        format!("Expected an array, got {} {}", v.type_name(), v),
+                    ))
+                }
+            };
+            match rest {
+                None if elements.len() != items.len() => {
+                    return Err(mismatch(
+                        pattern,
+                        format!(
+                            "Expected an array of {} elements, got {}",
+                            items.len(),
+                            elements.len()
+                        ),
+                    ))
+                }
+                Some(_) if elements.len() < items.len() => {
+                    return Err(mismatch(
+                        pattern,
+                        format!(
+                            "Expected an array of at least {} elements, got {}",
+                            items.len(),
+                            elements.len()
+                        ),
+                    ))
+                }
+                _ => {}
+            }
+
+            // Split the elements around what `..` soaks up.
+            let before = rest.as_ref().map_or(items.len(), |(i, _)| *i);
+            let tail = elements.split_off(elements.len() - (items.len() - before));
+            let middle = elements.split_off(before);
+            for (item, element) in items.iter().zip(elements.into_iter().chain(tail)) {
+                destructure_into(item, element, bindings)?;
+            }
+            if let Some((_, Some(name))) = rest {
+                bindings.push((name.clone(), pattern.span, Value::Array(middle)));
+            }
+        }
+        PatternKind::Map(entries) => {
+            let map = match value {
+                Value::Map(m) => m,
+                v => {
+                    return Err(mismatch(
+                        pattern,
+                        format!("Expected a map, got {} {}", v.type_name(), v),
+                    ))
+                }
+            };
+            for (key, item) in entries {
+                let key = Value::from(key.clone());
+                match map.get(&key) {
+                    Some(v) => destructure_into(item, v.clone(), bindings)?,
+                    None => return Err(mismatch(pattern, format!("Missing key {}", key))),
+                }
+            }
+        }
+    }
+    Ok(())
+}
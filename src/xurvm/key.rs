@@ -0,0 +1,37 @@
+use std::hash::{Hash, Hasher};
+
+use indexmap::Equivalent;
+
+use crate::xurvm::Value;
+
+/// A map key or set item. Keys are compared with `==`, and since equal values hash the
+/// same, `1` and `1.0` are the same key.
+#[derive(Clone, Debug)]
+pub struct Key(pub(super) Value);
+
+impl Key {
+    pub(super) fn into_value(self) -> Value {
+        self.0
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Key) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Key {}
+
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+/// Lets a plain `&Value` look up a key without cloning it.
+impl Equivalent<Key> for Value {
+    fn equivalent(&self, key: &Key) -> bool {
+        *self == key.0
+    }
+}
@@ -0,0 +1,60 @@
+use anyhow::bail;
+use indexmap::IndexMap;
+
+use crate::xurvm::key::Key;
+use crate::xurvm::Value;
+
+/// An insertion ordered map. Any value that can be compared with `==` can be a key,
+/// and keys are hashed so lookups stay fast for large maps.
+#[derive(Clone, Debug, Default)]
+pub struct Map {
+    entries: IndexMap<Key, Value>,
+}
+
+impl Map {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &Value) -> Option<&Value> {
+        self.entries.get(key)
+    }
+
+    /// Sets `key`, keeping its place if it is already in the map.
+    pub fn insert(&mut self, key: Value, value: Value) -> anyhow::Result<()> {
+        if matches!(key, Value::Function(_)) {
+            bail!("{} can't be a map key", key);
+        }
+        self.entries.insert(Key(key), value);
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Value, &Value)> {
+        self.entries.iter().map(|(k, v)| (&k.0, v))
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (Value, Value);
+    type IntoIter =
+        std::iter::Map<indexmap::map::IntoIter<Key, Value>, fn((Key, Value)) -> (Value, Value)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter().map(|(k, v)| (k.into_value(), v))
+    }
+}
+
+/// Maps are equal if they have the same entries, in any order.
+impl PartialEq for Map {
+    fn eq(&self, other: &Map) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
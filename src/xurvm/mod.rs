@@ -1,4 +1,15 @@
+mod builtins;
+mod destructure;
+mod key;
+mod map;
+mod number;
+mod scope;
+mod set;
 mod var;
 mod vmcore;
 
+pub use map::Map;
+pub use number::Number;
+pub use set::Set;
+pub use var::Value;
 pub use vmcore::XurVM;
@@ -0,0 +1,378 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use anyhow::bail;
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{FromPrimitive, Signed, ToPrimitive, Zero};
+
+/// The most bits `**` and `<<` may add to an integer. That's a result of about five
+/// million digits, around the biggest that still comes back in a second or so.
+const MAX_BITS: u64 = 1 << 24;
+
+/// Xur's numeric tower. Integers live in an `i64` until they overflow, at which point
+/// they are promoted to a big integer; big results that fit are demoted again.
+/// Any operation involving a float gives a float.
+#[derive(Clone, Debug)]
+pub enum Number {
+    Int(i64),
+    Big(Rc<BigInt>),
+    Float(f64),
+}
+
+impl std::fmt::Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Number::Int(n) => write!(f, "{}", n),
+            Number::Big(n) => write!(f, "{}", n),
+            // Debug keeps the `.0` on whole floats.
+            Number::Float(n) => write!(f, "{:?}", n),
+        }
+    }
+}
+
+impl From<i64> for Number {
+    fn from(n: i64) -> Self {
+        Number::Int(n)
+    }
+}
+
+impl From<f64> for Number {
+    fn from(n: f64) -> Self {
+        Number::Float(n)
+    }
+}
+
+impl From<BigInt> for Number {
+    fn from(n: BigInt) -> Self {
+        match n.to_i64() {
+            Some(n) => Number::Int(n),
+            None => Number::Big(Rc::new(n)),
+        }
+    }
+}
+
+impl From<usize> for Number {
+    fn from(n: usize) -> Self {
+        match i64::try_from(n) {
+            Ok(n) => Number::Int(n),
+            Err(_) => BigInt::from(n).into(),
+        }
+    }
+}
+
+/// Both operands coerced to the same representation.
+enum Pair {
+    Int(i64, i64),
+    Big(BigInt, BigInt),
+    Float(f64, f64),
+}
+
+impl Number {
+    fn check_radix(radix: u32) -> anyhow::Result<()> {
+        if !(2..=36).contains(&radix) {
+            bail!(
+                "Radix {} is out of range, it must be between 2 and 36",
+                radix
+            );
+        }
+        Ok(())
+    }
+
+    pub fn parse_radix(s: &str, radix: u32) -> anyhow::Result<Number> {
+        Self::check_radix(radix)?;
+        match BigInt::parse_bytes(s.as_bytes(), radix) {
+            Some(n) => Ok(n.into()),
+            None => bail!("\"{}\" is not a base {} integer", s, radix),
+        }
+    }
+
+    /// Parses a number the way it would be written in source, with an optional sign:
+    /// `42`, `1.5`, `1e3`, `0xff`, `0b101` or `0o17`.
+    pub fn parse(s: &str) -> anyhow::Result<Number> {
+        let trimmed = s.trim();
+        let (negative, unsigned) = match trimmed.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+        };
+        let radix = match unsigned.get(..2).map(|p| p.to_ascii_lowercase()).as_deref() {
+            Some("0x") => 16,
+            Some("0b") => 2,
+            Some("0o") => 8,
+            _ => 10,
+        };
+        let digits = if radix == 10 {
+            unsigned
+        } else {
+            &unsigned[2..]
+        };
+
+        let n = if !digits.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+            None
+        } else if radix != 10 || digits.chars().all(|c| c.is_ascii_digit()) {
+            BigInt::parse_bytes(digits.as_bytes(), radix).map(Number::from)
+        } else if digits.starts_with(|c: char| c.is_ascii_digit()) {
+            digits.parse::<f64>().ok().map(Number::Float)
+        } else {
+            // Rust would also read `inf` and `nan`.
+            None
+        };
+        match n {
+            Some(n) if negative => Ok(n.neg()),
+            Some(n) => Ok(n),
+            None => bail!("\"{}\" is not a number", s),
+        }
+    }
+
+    /// The digits of an integer in `radix`, with a `-` if it is negative. Floats can
+    /// only be written in base 10.
+    pub fn to_str_radix(&self, radix: u32) -> anyhow::Result<String> {
+        Self::check_radix(radix)?;
+        match self {
+            Number::Float(_) if radix == 10 => Ok(self.to_string()),
+            _ => Ok(self.to_bigint()?.to_str_radix(radix)),
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, Number::Float(_))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Number::Int(n) => *n == 0,
+            Number::Big(n) => n.is_zero(),
+            Number::Float(n) => *n == 0.0,
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Number::Int(n) => *n as f64,
+            Number::Big(n) => n.to_f64().unwrap_or(f64::NAN),
+            Number::Float(n) => *n,
+        }
+    }
+
+    pub fn to_bigint(&self) -> anyhow::Result<BigInt> {
+        match self {
+            Number::Int(n) => Ok(BigInt::from(*n)),
+            Number::Big(n) => Ok(n.as_ref().clone()),
+            Number::Float(n) => bail!("{:?} is not an integer", n),
+        }
+    }
+
+    pub fn to_i64(&self) -> anyhow::Result<i64> {
+        match self {
+            Number::Int(n) => Ok(*n),
+            Number::Big(n) => bail!("{} is too large", n),
+            Number::Float(n) => bail!("{:?} is not an integer", n),
+        }
+    }
+
+    /// For counts and indexes.
+    pub fn to_usize(&self) -> anyhow::Result<usize> {
+        let n = self.to_i64()?;
+        match usize::try_from(n) {
+            Ok(n) => Ok(n),
+            Err(_) => bail!("{} can't be negative", n),
+        }
+    }
+
+    fn pair(&self, other: &Number) -> Pair {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => Pair::Int(*a, *b),
+            (Number::Float(_), _) | (_, Number::Float(_)) => {
+                Pair::Float(self.to_f64(), other.to_f64())
+            }
+            _ => Pair::Big(
+                self.to_bigint().expect("not a float"),
+                other.to_bigint().expect("not a float"),
+            ),
+        }
+    }
+
+    fn int_op(
+        &self,
+        other: &Number,
+        checked: fn(i64, i64) -> Option<i64>,
+        big: fn(BigInt, BigInt) -> BigInt,
+        float: fn(f64, f64) -> f64,
+    ) -> Number {
+        match self.pair(other) {
+            Pair::Int(a, b) => match checked(a, b) {
+                Some(n) => Number::Int(n),
+                None => big(BigInt::from(a), BigInt::from(b)).into(),
+            },
+            Pair::Big(a, b) => big(a, b).into(),
+            Pair::Float(a, b) => Number::Float(float(a, b)),
+        }
+    }
+
+    pub fn add(&self, other: &Number) -> Number {
+        self.int_op(other, i64::checked_add, |a, b| a + b, |a, b| a + b)
+    }
+
+    pub fn sub(&self, other: &Number) -> Number {
+        self.int_op(other, i64::checked_sub, |a, b| a - b, |a, b| a - b)
+    }
+
+    pub fn mul(&self, other: &Number) -> Number {
+        self.int_op(other, i64::checked_mul, |a, b| a * b, |a, b| a * b)
+    }
+
+    pub fn neg(&self) -> Number {
+        match self {
+            Number::Int(n) => match n.checked_neg() {
+                Some(n) => Number::Int(n),
+                None => (-BigInt::from(*n)).into(),
+            },
+            Number::Big(n) => (-n.as_ref()).into(),
+            Number::Float(n) => Number::Float(-n),
+        }
+    }
+
+    /// True division, always a float.
+    pub fn div(&self, other: &Number) -> anyhow::Result<Number> {
+        if other.is_zero() {
+            bail!("Division by zero");
+        }
+        Ok(Number::Float(self.to_f64() / other.to_f64()))
+    }
+
+    /// Division rounded towards negative infinity.
+    pub fn floor_div(&self, other: &Number) -> anyhow::Result<Number> {
+        if other.is_zero() {
+            bail!("Division by zero");
+        }
+        Ok(match self.pair(other) {
+            // Only i64::MIN // -1 overflows.
+            Pair::Int(a, b) if a.checked_div(b).is_some() => {
+                Number::Int(Integer::div_floor(&a, &b))
+            }
+            Pair::Int(a, b) => BigInt::from(a).div_floor(&BigInt::from(b)).into(),
+            Pair::Big(a, b) => a.div_floor(&b).into(),
+            Pair::Float(a, b) => Number::Float((a / b).floor()),
+        })
+    }
+
+    /// The remainder of [`Number::floor_div`], which has the sign of the divisor.
+    pub fn modulo(&self, other: &Number) -> anyhow::Result<Number> {
+        if other.is_zero() {
+            bail!("Division by zero");
+        }
+        Ok(match self.pair(other) {
+            Pair::Int(a, b) if a.checked_rem(b).is_some() => Number::Int(a.mod_floor(&b)),
+            Pair::Int(a, b) => BigInt::from(a).mod_floor(&BigInt::from(b)).into(),
+            Pair::Big(a, b) => a.mod_floor(&b).into(),
+            Pair::Float(a, b) => Number::Float(a - b * (a / b).floor()),
+        })
+    }
+
+    /// Integer powers stay integers; negative or float exponents give a float.
+    pub fn pow(&self, other: &Number) -> anyhow::Result<Number> {
+        Ok(match self.pair(other) {
+            Pair::Float(a, b) => Number::Float(a.powf(b)),
+            _ if other.is_negative() => Number::Float(self.to_f64().powf(other.to_f64())),
+            _ => {
+                let base = self.to_bigint()?;
+                // 0, 1 and -1 stay small whatever the exponent.
+                let grows = base.magnitude().bits().saturating_sub(1);
+                let exp = match other.to_i64().ok().and_then(|e| u32::try_from(e).ok()) {
+                    Some(e) if grows.saturating_mul(e as u64) <= MAX_BITS => e,
+                    _ => bail!("Exponent {} is too large", other),
+                };
+                match self {
+                    Number::Int(n) => match n.checked_pow(exp) {
+                        Some(n) => Number::Int(n),
+                        None => BigInt::from(*n).pow(exp).into(),
+                    },
+                    _ => base.pow(exp).into(),
+                }
+            }
+        })
+    }
+
+    fn bit_op(
+        &self,
+        other: &Number,
+        int: fn(i64, i64) -> i64,
+        big: fn(BigInt, BigInt) -> BigInt,
+    ) -> anyhow::Result<Number> {
+        Ok(match self.pair(other) {
+            Pair::Int(a, b) => Number::Int(int(a, b)),
+            Pair::Big(a, b) => big(a, b).into(),
+            Pair::Float(..) => bail!(
+                "Bitwise operators need integers, got {} and {}",
+                self,
+                other
+            ),
+        })
+    }
+
+    pub fn bitand(&self, other: &Number) -> anyhow::Result<Number> {
+        self.bit_op(other, |a, b| a & b, |a, b| a & b)
+    }
+
+    pub fn bitor(&self, other: &Number) -> anyhow::Result<Number> {
+        self.bit_op(other, |a, b| a | b, |a, b| a | b)
+    }
+
+    pub fn bitxor(&self, other: &Number) -> anyhow::Result<Number> {
+        self.bit_op(other, |a, b| a ^ b, |a, b| a ^ b)
+    }
+
+    /// Shifts go through a big integer so `1 << 100` doesn't wrap.
+    pub fn shl(&self, other: &Number) -> anyhow::Result<Number> {
+        let n = self.to_bigint()?;
+        let shift = other.to_usize()?;
+        if !n.is_zero() && shift as u64 > MAX_BITS {
+            bail!("Shift {} is too large", other);
+        }
+        Ok((n << shift).into())
+    }
+
+    /// Arithmetic shift, rounding towards negative infinity like [`Number::floor_div`].
+    pub fn shr(&self, other: &Number) -> anyhow::Result<Number> {
+        Ok((self.to_bigint()? >> other.to_usize()?).into())
+    }
+
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Number::Int(n) => *n < 0,
+            Number::Big(n) => n.is_negative(),
+            Number::Float(n) => *n < 0.0,
+        }
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Number) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+/// Whole floats hash like the integer they equal, so `1` and `1.0` are one map key.
+impl Hash for Number {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Number::Int(n) => n.hash(state),
+            Number::Big(n) => n.hash(state),
+            Number::Float(n) => match BigInt::from_f64(*n) {
+                Some(whole) if n.fract() == 0.0 => Number::from(whole).hash(state),
+                _ => n.to_bits().hash(state),
+            },
+        }
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Number) -> Option<Ordering> {
+        match self.pair(other) {
+            Pair::Int(a, b) => Some(a.cmp(&b)),
+            Pair::Big(a, b) => Some(a.cmp(&b)),
+            Pair::Float(a, b) => a.partial_cmp(&b),
+        }
+    }
+}
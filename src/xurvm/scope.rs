@@ -0,0 +1,120 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use anyhow::bail;
+
+use crate::xurvm::var::Value;
+
+pub type ScopeRef = Rc<RefCell<Scope>>;
+
+#[derive(Clone, Debug)]
+pub struct Binding {
+    pub value: Value,
+    pub constant: bool,
+}
+
+/// One frame of bindings. Function calls and blocks each get a new frame whose parent
+/// is the scope they were defined in, so closures keep their surroundings alive.
+#[derive(Debug, Default)]
+pub struct Scope {
+    bindings: HashMap<String, Binding>,
+    parent: Option<ScopeRef>,
+    /// Set on the frame of a function call, which plain assignments don't reach past.
+    function: bool,
+}
+
+impl Scope {
+    pub fn new_ref(parent: Option<ScopeRef>) -> ScopeRef {
+        Rc::new(RefCell::new(Scope {
+            bindings: HashMap::new(),
+            parent,
+            function: false,
+        }))
+    }
+
+    /// The frame for a call to a function defined in `parent`.
+    pub fn new_function_ref(parent: ScopeRef) -> ScopeRef {
+        Rc::new(RefCell::new(Scope {
+            bindings: HashMap::new(),
+            parent: Some(parent),
+            function: true,
+        }))
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        match self.bindings.get(name) {
+            Some(b) => Some(b.value.clone()),
+            None => self.parent.as_ref()?.borrow().get(name),
+        }
+    }
+
+    /// Binds `name` in this frame, shadowing anything further out.
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.bindings.insert(
+            name.into(),
+            Binding {
+                value,
+                constant: false,
+            },
+        );
+    }
+
+    /// The bindings made directly in this frame.
+    pub fn locals(&self) -> impl Iterator<Item = (&String, &Binding)> {
+        self.bindings.iter()
+    }
+
+    /// Rebinds `name` in the nearest frame that already has it, or defines it in
+    /// `scope` if no frame does. The search stops at the enclosing function's frame, so
+    /// a function never rebinds its caller's variables. Constants are always defined in
+    /// `scope` itself.
+    pub fn assign(
+        scope: &ScopeRef,
+        name: &str,
+        value: Value,
+        constant: bool,
+    ) -> anyhow::Result<()> {
+        let target = if constant {
+            scope.clone()
+        } else {
+            Self::find(scope, name, false).unwrap_or_else(|| scope.clone())
+        };
+        Self::bind(&target, name, value, constant)
+    }
+
+    /// Rebinds `name` in the nearest frame that has it, however far out that is, as in
+    /// `nonlocal count = count + 1`.
+    pub fn assign_nonlocal(scope: &ScopeRef, name: &str, value: Value) -> anyhow::Result<()> {
+        match Self::find(scope, name, true) {
+            Some(target) => Self::bind(&target, name, value, false),
+            None => bail!("\"{}\" is not defined.", name),
+        }
+    }
+
+    fn bind(target: &ScopeRef, name: &str, value: Value, constant: bool) -> anyhow::Result<()> {
+        let mut target = target.borrow_mut();
+        if let Some(Binding { constant: true, .. }) = target.bindings.get(name) {
+            bail!("Cannot assign twice to constant \"{}\"", name);
+        }
+        target
+            .bindings
+            .insert(name.into(), Binding { value, constant });
+        Ok(())
+    }
+
+    /// The nearest frame binding `name`, looking past function frames only if
+    /// `past_functions` is set.
+    fn find(scope: &ScopeRef, name: &str, past_functions: bool) -> Option<ScopeRef> {
+        let frame = scope.borrow();
+        if frame.bindings.contains_key(name) {
+            return Some(scope.clone());
+        }
+        if frame.function && !past_functions {
+            return None;
+        }
+        let parent = frame.parent.clone()?;
+        drop(frame);
+        Self::find(&parent, name, past_functions)
+    }
+}
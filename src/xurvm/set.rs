@@ -0,0 +1,87 @@
+use anyhow::bail;
+use indexmap::IndexSet;
+
+use crate::xurvm::key::Key;
+use crate::xurvm::Value;
+
+/// An insertion ordered set, hashed like [`crate::xurvm::Map`]'s keys.
+#[derive(Clone, Debug, Default)]
+pub struct Set {
+    items: IndexSet<Key>,
+}
+
+impl Set {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_items(items: Vec<Value>) -> anyhow::Result<Self> {
+        let mut set = Set::new();
+        for item in items {
+            set.insert(item)?;
+        }
+        Ok(set)
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn contains(&self, value: &Value) -> bool {
+        self.items.contains(value)
+    }
+
+    /// Adds `value`, unless an equal item is already in the set.
+    pub fn insert(&mut self, value: Value) -> anyhow::Result<()> {
+        if matches!(value, Value::Function(_)) {
+            bail!("{} can't be put in a set", value);
+        }
+        self.items.insert(Key(value));
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Value> {
+        self.items.iter().map(|k| &k.0)
+    }
+
+    pub fn union(mut self, other: Set) -> Set {
+        self.items.extend(other.items);
+        self
+    }
+
+    pub fn intersect(self, other: &Set) -> Set {
+        let items = self.items.into_iter().filter(|i| other.items.contains(i));
+        Set {
+            items: items.collect(),
+        }
+    }
+
+    pub fn diff(self, other: &Set) -> Set {
+        let items = self.items.into_iter().filter(|i| !other.items.contains(i));
+        Set {
+            items: items.collect(),
+        }
+    }
+}
+
+impl IntoIterator for Set {
+    type Item = Value;
+    type IntoIter = std::iter::Map<indexmap::set::IntoIter<Key>, fn(Key) -> Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items
+            .into_iter()
+            .map(Key::into_value as fn(Key) -> Value)
+    }
+}
+
+/// Sets are equal if they have the same items, in any order.
+impl PartialEq for Set {
+    fn eq(&self, other: &Set) -> bool {
+        self.len() == other.len() && self.iter().all(|i| other.contains(i))
+    }
+}
@@ -1,24 +1,71 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
 use std::rc::Rc;
-use std::{collections::VecDeque, ops::Range};
 
-use crate::ast::value::Expression;
-use anyhow::{anyhow, bail, Context};
+use crate::ast::value::{Expression, ExpressionKind, LiteralValue};
+use crate::ast::{PrecedenceRef, Statement};
+use crate::lexer::Span;
+use crate::xurvm::destructure::PatternBinding;
+use crate::xurvm::scope::ScopeRef;
+use crate::xurvm::{Map, Number, Set};
+use anyhow::{bail, Context};
+use regex::Regex;
 use tracing::{debug, info};
 
 pub trait Enviroment {
-    fn get_value(&self, name: &str) -> Option<&Value>;
+    fn get_value(&self, name: &str) -> Option<Value>;
+    /// Binds `name`, failing if it is already bound as a constant.
+    fn set_value(&mut self, name: &str, value: Value, constant: bool) -> anyhow::Result<()>;
+    /// Binds `name` in the current scope, shadowing any outer binding. Fails if the
+    /// current scope already has `name` as a constant.
+    fn define_value(&mut self, name: &str, value: Value) -> anyhow::Result<()>;
+    /// The scope currently being executed, for closures to hold on to.
+    fn current_scope(&self) -> ScopeRef;
+    /// The precedence of inline functions, shared with the parser.
+    fn precedence(&self) -> PrecedenceRef;
     fn resolve_expression(&mut self, ex: Expression) -> anyhow::Result<Value>;
     fn resolve_args(&mut self, args: Vec<Expression>) -> anyhow::Result<Vec<Value>>;
+    /// Evaluates `ex` in a new child scope with `bindings` defined in it.
+    fn resolve_with_bindings(
+        &mut self,
+        bindings: Vec<PatternBinding>,
+        ex: Expression,
+    ) -> anyhow::Result<Value>;
+    /// Runs `statements` in a new child scope and returns that scope.
+    fn run_scoped(&mut self, statements: Vec<Statement>) -> anyhow::Result<ScopeRef>;
+    /// Runs the body of a user defined function with its parameters bound to `args`.
+    fn call_lambda(&mut self, lambda: &Lambda, args: Vec<Value>) -> anyhow::Result<Value>;
+    /// Compiles `pattern`, reusing the regex from any earlier call with the same pattern.
+    fn regex(&mut self, pattern: &str) -> anyhow::Result<Rc<Regex>>;
 }
 
 pub type CallableHanderF = fn(&mut dyn Enviroment, Vec<Expression>) -> anyhow::Result<Value>;
 
+/// A function defined in xur with `fn(name, (params), { body })`.
+pub struct Lambda {
+    pub params: Vec<String>,
+    pub body: Vec<Statement>,
+    /// The scope `fn` was called in. Calls run in a child of it.
+    pub scope: ScopeRef,
+}
+
+#[derive(Clone)]
+pub enum CallTarget {
+    Native(CallableHanderF),
+    Lambda(Rc<Lambda>),
+}
+
 #[derive(Clone)]
 pub struct Callable {
     id: String,
     expected_args: (usize, usize),
-    target: CallableHanderF,
+    target: CallTarget,
     binded_args: Vec<Expression>,
+    /// Lazy functions get their arguments as written. Everything else has its bound
+    /// arguments evaluated as soon as it is partially applied.
+    lazy: bool,
 }
 
 impl std::fmt::Debug for Callable {
@@ -27,17 +74,49 @@ impl std::fmt::Debug for Callable {
     }
 }
 
-fn transfer_expression_list(destination: &mut Vec<Expression>, source: Vec<Expression>, fill_list: &mut VecDeque<Expression>) {
+fn transfer_expression_list(
+    destination: &mut Vec<Expression>,
+    source: Vec<Expression>,
+    fill_list: &mut VecDeque<Expression>,
+) {
     for ex in source.into_iter() {
-        match ex {
-            Expression::None if !fill_list.is_empty() 
-            => destination.push(fill_list.pop_front().expect("Failed to pop arg in call")),
-            x => destination.push(x),
+        if ex.is_none() && !fill_list.is_empty() {
+            destination.push(fill_list.pop_front().expect("Failed to pop arg in call"));
+        } else {
+            destination.push(ex);
         }
     }
 }
 
 impl Callable {
+    pub fn native(name: &str, expected_args: (usize, usize), f: CallableHanderF) -> Self {
+        Self {
+            id: name.into(),
+            expected_args,
+            target: CallTarget::Native(f),
+            binded_args: Vec::new(),
+            lazy: false,
+        }
+    }
+
+    /// A native function that is handed its arguments unevaluated, even when partial.
+    pub fn lazy_native(name: &str, expected_args: (usize, usize), f: CallableHanderF) -> Self {
+        Self {
+            lazy: true,
+            ..Self::native(name, expected_args, f)
+        }
+    }
+
+    pub fn lambda(name: &str, lambda: Lambda) -> Self {
+        Self {
+            id: name.into(),
+            expected_args: (lambda.params.len(), lambda.params.len()),
+            target: CallTarget::Lambda(Rc::new(lambda)),
+            binded_args: Vec::new(),
+            lazy: false,
+        }
+    }
+
     pub fn args_fit(&self, n: usize) -> bool {
         n >= self.expected_args.0 && n <= self.expected_args.1
     }
@@ -48,34 +127,43 @@ impl Callable {
         &self.id
     }
 
-
     #[tracing::instrument(skip(self, env))]
     pub fn call(
         mut self,
         env: &mut dyn Enviroment,
         args: Vec<Expression>,
     ) -> anyhow::Result<Value> {
-        println!("Inargs: {:?}", &args);
+        debug!("Inargs: {:?}", &args);
 
-        if !self.binded_args.is_empty() {
+        if self.lazy {
+            // Lazy functions see parameter sets as written, so only fill holes.
+            let mut v = VecDeque::from(args);
+            for slot in self.binded_args.iter_mut().filter(|a| a.is_none()) {
+                match v.pop_front() {
+                    Some(a) => *slot = a,
+                    None => break,
+                }
+            }
+            self.binded_args.extend(v);
+        } else if !self.binded_args.is_empty() {
             let mut new_args = Vec::new();
             let mut v = VecDeque::from(args);
 
             for e in self.binded_args.into_iter() {
-                match e {
-                    Expression::None if !v.is_empty() => {
+                match e.kind {
+                    ExpressionKind::None if !v.is_empty() => {
                         new_args.push(v.pop_front().context("Failed to pop arg in call")?)
                     }
-                    Expression::ParenList(p) => {
+                    ExpressionKind::ParenList(p) => {
                         transfer_expression_list(&mut new_args, p, &mut v);
                     }
-                    x => new_args.push(x),
+                    _ => new_args.push(e),
                 }
             }
             for e in v.into_iter() {
-                match e {
-                    Expression::ParenList(mut p) => new_args.append(&mut p),
-                    x => new_args.push(x),
+                match e.kind {
+                    ExpressionKind::ParenList(mut p) => new_args.append(&mut p),
+                    _ => new_args.push(e),
                 }
             }
             self.binded_args = new_args;
@@ -83,47 +171,96 @@ impl Callable {
             self.binded_args.clear();
             let mut v = VecDeque::from(args);
             while let Some(ex) = v.pop_front() {
-                match ex {
-                    Expression::ParenList(p) => {
+                match ex.kind {
+                    ExpressionKind::ParenList(p) => {
                         transfer_expression_list(&mut self.binded_args, p, &mut v);
-                    },
-                    x => self.binded_args.push(x)
+                    }
+                    _ => self.binded_args.push(ex),
                 }
             }
         };
 
-        let binded_arg_count = self
+        let binded_arg_count = self.binded_args.iter().filter(|a| !a.is_none()).count();
+        // A hole before the last argument always leaves the call partial, even if the
+        // function could be called with fewer arguments, e.g. `if(, a, b)`.
+        let has_gap = self
             .binded_args
             .iter()
-            .filter(|a| !matches!(a, Expression::None))
-            .count();
+            .rev()
+            .skip_while(|a| a.is_none())
+            .any(|a| a.is_none());
 
-        println!("Callinf {} with {:?}", &self.name(), &self.binded_args);
+        debug!("Calling {} with {:?}", &self.name(), &self.binded_args);
 
-        if !self.args_fit(binded_arg_count) {
+        if has_gap || !self.args_fit(binded_arg_count) {
             info!(
                 "\"{}\" expected {:?} args, got {}",
                 &self.name(),
                 self.min_args(),
                 binded_arg_count
             );
+            if !self.lazy {
+                self.capture_args(env)?;
+            }
             return Ok(Value::Function(self));
         }
 
-        debug!("Calling {}", self.name());
-
         let ctx_name = self.name().to_string();
-        let r = (&self.target)(env, self.binded_args);
-        r.context(format!("Fn \"{}\"", ctx_name))
+        let r = match self.target {
+            CallTarget::Native(f) => f(env, self.binded_args),
+            CallTarget::Lambda(lambda) => {
+                let args: Vec<Expression> = self
+                    .binded_args
+                    .into_iter()
+                    .filter(|a| !a.is_none())
+                    .collect();
+                env.resolve_args(args)
+                    .and_then(|args| env.call_lambda(&lambda, args))
+            }
+        };
+        r.context(format!("while calling \"{}\"", ctx_name))
+    }
+
+    /// Calls the function with arguments that are already evaluated, as builtins like
+    /// `map` do.
+    pub fn call_values(self, env: &mut dyn Enviroment, args: Vec<Value>) -> anyhow::Result<Value> {
+        let args = args
+            .into_iter()
+            .map(|v| Expression::new(ExpressionKind::Value(v), Span::default()))
+            .collect();
+        self.call(env, args)
+    }
+
+    /// Evaluates the bound arguments now, so a partial function keeps working once the
+    /// scope it was made in has ended.
+    fn capture_args(&mut self, env: &mut dyn Enviroment) -> anyhow::Result<()> {
+        for arg in self.binded_args.iter_mut() {
+            if matches!(
+                arg.kind,
+                ExpressionKind::None | ExpressionKind::ParenList(_) | ExpressionKind::Value(_)
+            ) {
+                continue;
+            }
+            let value = env.resolve_expression(arg.clone())?;
+            *arg = Expression::new(ExpressionKind::Value(value), arg.span);
+        }
+        Ok(())
     }
 }
 
 #[derive(Clone)]
 pub enum Value {
-    Number(u128),
+    Number(Number),
+    Bool(bool),
     String(Rc<String>),
+    Bytes(Rc<Vec<u8>>),
     Function(Callable),
     Array(Vec<Value>),
+    Tuple(Vec<Value>),
+    Set(Set),
+    Map(Map),
+    /// A compiled `re("...")` pattern.
+    Regex(Rc<Regex>),
     None,
 }
 
@@ -131,40 +268,324 @@ impl std::fmt::Debug for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Value::Number(n) => write!(f, "Value({})", n),
+            Value::Bool(b) => write!(f, "Value({})", b),
             Value::String(n) => write!(f, "Value({})", n),
+            Value::Bytes(b) => write!(f, "Value({:?})", b),
             Value::Array(n) => write!(f, "Value({:?})", n),
+            Value::Tuple(t) => write!(f, "Value(Tuple({:?}))", t),
+            Value::Set(s) => write!(f, "Value({:?})", s),
+            Value::Map(m) => write!(f, "Value({:?})", m),
+            Value::Regex(r) => write!(f, "Value({:?})", r),
             Value::None => write!(f, "Value(None)"),
             _ => write!(f, "Value(?)"),
         }
     }
 }
 
+fn write_items<'a>(
+    f: &mut std::fmt::Formatter,
+    items: impl Iterator<Item = &'a Value>,
+) -> std::fmt::Result {
+    for (i, v) in items.enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", v)?;
+    }
+    Ok(())
+}
+
+/// A [`Value`] as it reads in text, see [`Value::display`].
+pub struct Display<'a>(&'a Value);
+
+impl std::fmt::Display for Display<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.0 {
+            Value::String(s) => write!(f, "{}", s),
+            v => write!(f, "{}", v),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::String(s) => write!(f, "{:?}", s),
+            Value::Bytes(b) => {
+                write!(f, "b\"")?;
+                for byte in b.iter() {
+                    match byte {
+                        b'"' => write!(f, "\\\"")?,
+                        b'\\' => write!(f, "\\\\")?,
+                        b'\n' => write!(f, "\\n")?,
+                        b'\t' => write!(f, "\\t")?,
+                        b'\r' => write!(f, "\\r")?,
+                        0x20..=0x7e => write!(f, "{}", *byte as char)?,
+                        _ => write!(f, "\\x{:02x}", byte)?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            Value::Function(c) => write!(f, "<fn {}>", c.name()),
+            Value::Array(a) => {
+                write!(f, "[")?;
+                write_items(f, a.iter())?;
+                write!(f, "]")
+            }
+            Value::Tuple(t) => {
+                write!(f, "@(")?;
+                write_items(f, t.iter())?;
+                write!(f, ")")
+            }
+            Value::Set(s) => {
+                write!(f, "to_set([")?;
+                write_items(f, s.iter())?;
+                write!(f, "])")
+            }
+            Value::Map(m) => {
+                // A `{` only reads back as a map when it starts with a literal key.
+                match m.iter().next() {
+                    Some((k, _)) if k.is_literal_key() => write!(f, "{{")?,
+                    _ => write!(f, "@{{")?,
+                }
+                for (i, (k, v)) in m.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", k, v)?;
+                }
+                write!(f, "}}")
+            }
+            Value::Regex(r) => write!(f, "re({:?})", r.as_str()),
+            Value::None => write!(f, "none"),
+        }
+    }
+}
+
+impl From<LiteralValue> for Value {
+    fn from(literal: LiteralValue) -> Self {
+        match literal {
+            LiteralValue::Number(n) => Value::Number(n),
+            LiteralValue::String(s) => Value::String(s),
+            LiteralValue::Bytes(b) => Value::Bytes(b),
+            LiteralValue::Boolean(b) => Value::Bool(b),
+            LiteralValue::None => Value::None,
+        }
+    }
+}
+
+/// Functions are never equal, not even to themselves.
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Bytes(a), Value::Bytes(b)) => a == b,
+            (Value::Array(a), Value::Array(b)) => a == b,
+            (Value::Tuple(a), Value::Tuple(b)) => a == b,
+            (Value::Set(a), Value::Set(b)) => a == b,
+            (Value::Map(a), Value::Map(b)) => a == b,
+            (Value::Regex(a), Value::Regex(b)) => a.as_str() == b.as_str(),
+            (Value::None, Value::None) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Values that are `==` hash the same. Sets and maps hash the same whatever order their
+/// items are in, and functions, which are never equal, hash alike.
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Value::Number(n) => n.hash(state),
+            Value::Bool(b) => b.hash(state),
+            Value::String(s) => s.hash(state),
+            Value::Bytes(b) => b.hash(state),
+            Value::Array(items) | Value::Tuple(items) => items.hash(state),
+            Value::Set(s) => unordered_hash(s.iter(), state),
+            Value::Map(m) => unordered_hash(m.iter(), state),
+            Value::Regex(r) => r.as_str().hash(state),
+            Value::Function(_) | Value::None => {}
+        }
+    }
+}
+
+/// Adds up the hash of each entry on its own, so their order doesn't matter.
+fn unordered_hash<H: Hasher>(entries: impl Iterator<Item = impl Hash>, state: &mut H) {
+    let mut count: usize = 0;
+    let mut sum: u64 = 0;
+    for entry in entries {
+        let mut hasher = DefaultHasher::new();
+        entry.hash(&mut hasher);
+        sum = sum.wrapping_add(hasher.finish());
+        count += 1;
+    }
+    count.hash(state);
+    sum.hash(state);
+}
+
 impl Value {
+    /// True if the value is written as a literal that the parser sees as the start of a
+    /// map, as in `{"a": 1}`.
+    fn is_literal_key(&self) -> bool {
+        match self {
+            Value::String(_) | Value::Bool(_) | Value::None => true,
+            Value::Number(Number::Float(n)) => n.is_finite() && n.is_sign_positive(),
+            Value::Number(n) => !n.is_negative(),
+            _ => false,
+        }
+    }
+
+    pub fn string(s: impl Into<String>) -> Value {
+        Value::String(Rc::new(s.into()))
+    }
+
     pub fn create_fn(name: &str, arg_n: usize, f: CallableHanderF) -> Value {
-        Value::Function(Callable {
-            id: name.into(),
-            target: f,
-            expected_args: (arg_n, arg_n),
-            binded_args: Vec::new(),
-        })
+        Value::Function(Callable::native(name, (arg_n, arg_n), f))
+    }
+    /// Formats like `Display`, except a string is written without quotes or escapes,
+    /// as in `f"..."` strings. Strings inside collections keep their quotes.
+    pub fn display(&self) -> Display<'_> {
+        Display(self)
     }
+
+    /// The name `->` and `zn` use for this value's type.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "number",
+            Value::Bool(_) => "bool",
+            Value::String(_) => "string",
+            Value::Bytes(_) => "bytes",
+            Value::Function(_) => "function",
+            Value::Array(_) => "array",
+            Value::Tuple(_) => "tuple",
+            Value::Set(_) => "set",
+            Value::Map(_) => "map",
+            Value::Regex(_) => "regex",
+            Value::None => "none",
+        }
+    }
+
     pub fn get_callable(self) -> anyhow::Result<Callable> {
         match self {
             Value::Function(f) => Ok(f),
-            x => bail!("Value {:?} is not callable", x),
+            x => bail!("{} is not callable", x),
         }
     }
 
-    pub fn get_number(self) -> anyhow::Result<u128> {
+    pub fn get_number(self) -> anyhow::Result<Number> {
         match self {
             Value::Number(f) => Ok(f),
-            x => bail!("Value {:?} is not number", x),
+            x => bail!("{} is not a number", x),
+        }
+    }
+    pub fn get_int(self) -> anyhow::Result<i64> {
+        self.get_number()?.to_i64()
+    }
+    pub fn get_usize(self) -> anyhow::Result<usize> {
+        self.get_number()?.to_usize()
+    }
+    pub fn get_bool(self) -> anyhow::Result<bool> {
+        match self {
+            Value::Bool(b) => Ok(b),
+            x => bail!("{} is not a bool", x),
         }
     }
     pub fn get_string(self) -> anyhow::Result<Rc<String>> {
         match self {
             Value::String(f) => Ok(f.clone()),
-            x => bail!("Value {:?} is not a string", x),
+            x => bail!("{} is not a string", x),
+        }
+    }
+    pub fn get_bytes(self) -> anyhow::Result<Rc<Vec<u8>>> {
+        match self {
+            Value::Bytes(b) => Ok(b),
+            x => bail!("{} is not bytes", x),
+        }
+    }
+    pub fn get_set(self) -> anyhow::Result<Set> {
+        match self {
+            Value::Set(s) => Ok(s),
+            x => bail!("{} is not a set", x),
+        }
+    }
+    pub fn get_map(self) -> anyhow::Result<Map> {
+        match self {
+            Value::Map(m) => Ok(m),
+            x => bail!("{} is not a map", x),
+        }
+    }
+    pub fn get_array(self) -> anyhow::Result<Vec<Value>> {
+        match self {
+            Value::Array(a) => Ok(a),
+            x => bail!("{} is not an array", x),
+        }
+    }
+
+    /// The elements of an array, tuple or set, the characters of a string, the numbers
+    /// in bytes, or the `[key, value]` pairs of a map.
+    pub fn get_items(self) -> anyhow::Result<Vec<Value>> {
+        match self {
+            Value::Array(a) | Value::Tuple(a) => Ok(a),
+            Value::Set(s) => Ok(s.into_iter().collect()),
+            Value::Bytes(b) => Ok(b
+                .iter()
+                .map(|n| Value::Number(Number::Int(*n as i64)))
+                .collect()),
+            Value::Map(m) => Ok(m
+                .into_iter()
+                .map(|(k, v)| Value::Array(vec![k, v]))
+                .collect()),
+            Value::String(s) => Ok(s
+                .chars()
+                .map(|c| Value::String(Rc::new(c.to_string())))
+                .collect()),
+            x => bail!("{} is not a collection", x),
+        }
+    }
+
+    /// Orders numbers by value, strings and bytes by their contents, and arrays and
+    /// tuples element by element. Anything else can't be ordered.
+    pub fn compare(&self, other: &Value) -> anyhow::Result<Ordering> {
+        let ordering = match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+            (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+            (Value::Bytes(a), Value::Bytes(b)) => Some(a.cmp(b)),
+            (Value::Array(a), Value::Array(b)) | (Value::Tuple(a), Value::Tuple(b)) => {
+                for (x, y) in a.iter().zip(b) {
+                    match x.compare(y)? {
+                        Ordering::Equal => continue,
+                        o => return Ok(o),
+                    }
+                }
+                Some(a.len().cmp(&b.len()))
+            }
+            _ => None,
+        };
+        match ordering {
+            Some(o) => Ok(o),
+            None => bail!("Can't compare {} with {}", self, other),
+        }
+    }
+
+    /// Whether the value counts as true for `filter`, `any` and friends. `false`, zero,
+    /// empty collections, and `none` are false.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Number(n) => !n.is_zero(),
+            Value::Bool(b) => *b,
+            Value::String(s) => !s.is_empty(),
+            Value::Bytes(b) => !b.is_empty(),
+            Value::Array(a) | Value::Tuple(a) => !a.is_empty(),
+            Value::Set(s) => !s.is_empty(),
+            Value::Map(m) => !m.is_empty(),
+            Value::Function(_) | Value::Regex(_) => true,
+            Value::None => false,
         }
     }
 }
@@ -1,48 +1,219 @@
-use std::collections::HashMap;
-use std::marker::PhantomData;
-
-use crate::ast::value::{Expression, LiteralValue};
-use crate::ast::Statement;
-use crate::xurvm::var::{CallableHanderF, Enviroment, Value};
-use anyhow::{anyhow, bail, Context};
-use tracing::{debug, info};
+use crate::ast::value::{Expression, ExpressionKind, LiteralValue};
+use crate::ast::{AssignKind, Associativity, Pattern, PrecedenceRef, PrecedenceTable, Statement};
+use crate::diagnostic::Diagnostic;
+use crate::xurvm::builtins;
+use crate::xurvm::destructure::{destructure, PatternBinding};
+use crate::xurvm::number::Number;
+use crate::xurvm::scope::{Scope, ScopeRef};
+use crate::xurvm::var::{Callable, CallableHanderF, Enviroment, Lambda, Value};
+use crate::xurvm::Map;
+use anyhow::{bail, Context};
+use indexmap::IndexMap;
+use regex::Regex;
+use std::rc::Rc;
+use tracing::debug;
 
 #[derive(Debug)]
 pub struct XurVM {
-    global_state: HashMap<String, Value>,
+    global_state: ScopeRef,
+    scope: ScopeRef,
+    precedence: PrecedenceRef,
+    /// Recently compiled regexes by pattern, least recently used first, so matching in a
+    /// loop doesn't recompile.
+    regexes: IndexMap<String, Rc<Regex>>,
 }
 
+/// How many compiled regexes are kept. A loop only needs the few patterns it uses, and
+/// older ones are dropped so generated patterns don't pile up over a long session.
+const REGEX_CACHE_SIZE: usize = 32;
+
 impl Enviroment for XurVM {
-    fn get_value(&self, name: &str) -> Option<&Value> {
-        self.global_state.get(name)
+    fn get_value(&self, name: &str) -> Option<Value> {
+        self.scope.borrow().get(name)
+    }
+
+    fn set_value(&mut self, name: &str, value: Value, constant: bool) -> anyhow::Result<()> {
+        Scope::assign(&self.scope, name, value, constant)
+    }
+
+    fn define_value(&mut self, name: &str, value: Value) -> anyhow::Result<()> {
+        let mut scope = self.scope.borrow_mut();
+        if scope.locals().any(|(n, b)| n == name && b.constant) {
+            bail!("Cannot assign twice to constant \"{}\"", name);
+        }
+        scope.define(name, value);
+        Ok(())
+    }
+
+    fn current_scope(&self) -> ScopeRef {
+        self.scope.clone()
+    }
+
+    fn precedence(&self) -> PrecedenceRef {
+        self.precedence.clone()
     }
 
     fn resolve_expression(&mut self, ex: Expression) -> anyhow::Result<Value> {
         self.get_value(ex)
     }
     fn resolve_args(&mut self, args: Vec<Expression>) -> anyhow::Result<Vec<Value>> {
-        let values: anyhow::Result<Vec<Value>> = args
-            .into_iter()
-            .flat_map(|x| match x {
-                Expression::ParenList(ex) => ex,
-                x => vec![x],
+        args.into_iter()
+            .flat_map(|x| match x.kind {
+                ExpressionKind::ParenList(ex) => ex,
+                _ => vec![x],
             })
             .map(|x| self.get_value(x))
-            .collect();
-        Ok(values?)
+            .collect()
+    }
+
+    fn resolve_with_bindings(
+        &mut self,
+        bindings: Vec<PatternBinding>,
+        ex: Expression,
+    ) -> anyhow::Result<Value> {
+        let frame = Scope::new_ref(Some(self.scope.clone()));
+        for (name, _, value) in bindings {
+            frame.borrow_mut().define(&name, value);
+        }
+        self.run_in_scope(frame, vec![Statement::Expr(ex)])
+    }
+
+    fn run_scoped(&mut self, statements: Vec<Statement>) -> anyhow::Result<ScopeRef> {
+        let frame = Scope::new_ref(Some(self.scope.clone()));
+        self.run_in_scope(frame.clone(), statements)?;
+        Ok(frame)
+    }
+
+    fn call_lambda(&mut self, lambda: &Lambda, args: Vec<Value>) -> anyhow::Result<Value> {
+        if args.len() != lambda.params.len() {
+            bail!(
+                "Expected {} arguments, got {}",
+                lambda.params.len(),
+                args.len()
+            );
+        }
+
+        let frame = Scope::new_function_ref(lambda.scope.clone());
+        for (param, value) in lambda.params.iter().zip(args) {
+            frame.borrow_mut().define(param, value);
+        }
+        self.run_in_scope(frame, lambda.body.clone())
+    }
+
+    fn regex(&mut self, pattern: &str) -> anyhow::Result<Rc<Regex>> {
+        if let Some(i) = self.regexes.get_index_of(pattern) {
+            let last = self.regexes.len() - 1;
+            self.regexes.move_index(i, last);
+            return Ok(self.regexes[last].clone());
+        }
+        let re = match Regex::new(pattern) {
+            Ok(re) => Rc::new(re),
+            // The last line of a syntax error says what is wrong, the rest repeats the pattern.
+            Err(e) => bail!(
+                "Invalid regex {:?}: {}",
+                pattern,
+                e.to_string()
+                    .lines()
+                    .last()
+                    .unwrap_or_default()
+                    .trim_start_matches("error: ")
+            ),
+        };
+        if self.regexes.len() == REGEX_CACHE_SIZE {
+            self.regexes.shift_remove_index(0);
+        }
+        self.regexes.insert(pattern.to_string(), re.clone());
+        Ok(re)
+    }
+}
+
+/// The name a function `name` defined in `zn(namespace, ...)` is bound to.
+fn mangle_name(namespace: &str, name: &str) -> String {
+    format!("__{}__{}", namespace, name)
+}
+
+/// Whether a `match` arm is a pattern to destructure with rather than a value to
+/// compare against.
+fn is_destructuring(arm: &Expression) -> bool {
+    match &arm.kind {
+        ExpressionKind::Array(_) | ExpressionKind::Tuple(_) | ExpressionKind::Map(_) => true,
+        ExpressionKind::Variable(name) => name == "_",
+        _ => false,
+    }
+}
+
+/// Backs `infixl` and `infixr`. Takes effect from the next statement parsed.
+fn set_precedence(
+    env: &mut dyn Enviroment,
+    args: Vec<Expression>,
+    associativity: Associativity,
+) -> anyhow::Result<Value> {
+    let mut args = env.resolve_args(args)?;
+    let precedence = args.pop().context("Failed to pop arg1")?.get_int()?;
+    let name = args.pop().context("Failed to pop arg2")?.get_string()?;
+    let precedence = match u8::try_from(precedence) {
+        Ok(p) => p,
+        Err(_) => bail!("Precedence must be between 0 and 255, got {}", precedence),
+    };
+    env.precedence()
+        .borrow_mut()
+        .set(&name, precedence, associativity);
+    Ok(Value::None)
+}
+
+/// Puts the left hand side of a method call into the first hole of its arguments,
+/// or in front of them if there is no hole. `a.f(,1)` is `f(a, 1)`, as is `a.f(1)`.
+fn insert_receiver(mut args: Vec<Expression>, receiver: Expression) -> Vec<Expression> {
+    match args.iter().position(|a| a.is_none()) {
+        Some(hole) if !matches!(receiver.kind, ExpressionKind::ParenList(_)) => {
+            args[hole] = receiver
+        }
+        _ => args.insert(0, receiver),
+    }
+    args
+}
+
+impl Default for XurVM {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl XurVM {
     pub fn new() -> Self {
+        let global_state = Scope::new_ref(None);
         Self {
-            global_state: HashMap::new(),
+            scope: global_state.clone(),
+            global_state,
+            precedence: PrecedenceTable::new_ref(),
+            regexes: IndexMap::new(),
         }
         .add_builtins()
     }
+
+    /// The table the parser should use so `infixl` and `infixr` take effect.
+    pub fn precedence(&self) -> PrecedenceRef {
+        self.precedence.clone()
+    }
     pub fn add_fn(&mut self, name: &str, arg_n: usize, f: CallableHanderF) {
         self.global_state
-            .insert(name.into(), Value::create_fn(name, arg_n, f));
+            .borrow_mut()
+            .define(name, Value::create_fn(name, arg_n, f));
+    }
+
+    /// Adds a function that is called once it has at least `min_args` arguments.
+    pub fn add_variadic_fn(&mut self, name: &str, min_args: usize, f: CallableHanderF) {
+        self.global_state.borrow_mut().define(
+            name,
+            Value::Function(Callable::native(name, (min_args, usize::MAX), f)),
+        );
+    }
+
+    /// Adds a function that is handed its arguments unevaluated. See [`Callable::lazy_native`].
+    pub fn add_lazy_fn(&mut self, name: &str, args: (usize, usize), f: CallableHanderF) {
+        self.global_state
+            .borrow_mut()
+            .define(name, Value::Function(Callable::lazy_native(name, args, f)));
     }
 
     fn add_builtins(mut self) -> Self {
@@ -50,7 +221,7 @@ impl XurVM {
             let mut args = env.resolve_args(args)?;
             let value_1 = args.pop().context("Failed to pop arg1")?.get_number()?;
             let value_2 = args.pop().context("Failed to pop arg2")?.get_number()?;
-            Ok(Value::Number(value_1 + value_2))
+            Ok(Value::Number(value_2.add(&value_1)))
         });
         self.add_fn("%", 1, |env, args| {
             let mut args = env.resolve_args(args)?;
@@ -58,8 +229,7 @@ impl XurVM {
 
             let binding = env
                 .get_value(value_1.as_ref())
-                .context(format!("Failed to get binding for \"{}\"", &value_1))?
-                .clone();
+                .context(format!("Failed to get binding for \"{}\"", &value_1))?;
 
             let fn_ref = binding.get_callable().context(format!(
                 "Faiiled to convert \"{}\" bind to callable.",
@@ -68,32 +238,147 @@ impl XurVM {
 
             Ok(Value::Function(fn_ref))
         });
-        self.add_fn(".", 2, |env, mut args| {
+        // a.b(c) is b(a, c)
+        self.add_lazy_fn(".", (2, 2), |env, mut args| {
             let value_1 = args.pop().context("Failed to pop arg1")?;
             let value_2 = args.pop().context("Failed to pop arg2")?;
-            println!(".call {:?} {:?}", value_1, value_2);
+            debug!(".call {:?} {:?}", value_1, value_2);
+
+            let span = value_2.span.to(value_1.span);
+            let (callie, args) = match value_1.kind {
+                ExpressionKind::Call { callie, args } => (callie, args),
+                ExpressionKind::Variable(_) => (Box::new(value_1), Vec::new()),
+                _ => {
+                    return Err(
+                        Diagnostic::error("Expected a function to call", value_1.span)
+                            .with_primary_label("not a function name or call")
+                            .into(),
+                    )
+                }
+            };
+
+            let receiver = match value_2.kind {
+                // A parameter set is expanded by the call itself, filling its own holes.
+                ExpressionKind::ParenList(_) => value_2,
+                _ => {
+                    let receiver_span = value_2.span;
+                    let receiver = env.resolve_expression(value_2)?;
+                    Expression::new(ExpressionKind::Value(receiver), receiver_span)
+                }
+            };
 
-            let expr = match value_1 {
-                Expression::Call { callie, mut args } => {
-                    args.insert(0, value_2);
-                    Expression::Call { callie, args }
+            let args = insert_receiver(args, receiver);
+            env.resolve_expression(Expression::new(ExpressionKind::Call { callie, args }, span))
+        });
+        // "ff"->to_int(16) is __string__to_int("ff", 16)
+        self.add_lazy_fn("->", (2, 2), |env, mut args| {
+            let method = args.pop().context("Failed to pop method")?;
+            let receiver = args.pop().context("Failed to pop receiver")?;
+            let receiver_span = receiver.span;
+            let receiver = env.resolve_expression(receiver)?;
+
+            let (name, name_span, call_args) = match method.kind {
+                ExpressionKind::Call { callie, args } => match callie.kind {
+                    ExpressionKind::Variable(name) => (name, callie.span, args),
+                    _ => {
+                        return Err(Diagnostic::error("Expected a method name", callie.span)
+                            .with_primary_label("not a name")
+                            .into())
+                    }
+                },
+                ExpressionKind::Variable(name) => (name, method.span, Vec::new()),
+                _ => {
+                    return Err(Diagnostic::error("Expected a method name", method.span)
+                        .with_primary_label("not a name")
+                        .into())
+                }
+            };
+
+            let type_name = receiver.type_name();
+            let mangled = mangle_name(type_name, &name);
+            if env.get_value(&mangled).is_none() {
+                return Err(Diagnostic::error(
+                    format!("No method \"{}\" for type {}", name, type_name),
+                    name_span,
+                )
+                .with_primary_label(format!("\"{}\" is not defined", mangled))
+                .with_note(format!(
+                    "methods are defined with zn({}, {{ fn({}, ...) }})",
+                    type_name, name
+                ))
+                .into());
+            }
+
+            let call_args = insert_receiver(
+                call_args,
+                Expression::new(ExpressionKind::Value(receiver), receiver_span),
+            );
+            let span = receiver_span.to(method.span);
+            env.resolve_expression(Expression::new(
+                ExpressionKind::Call {
+                    callie: Box::new(Expression::new(
+                        ExpressionKind::Variable(mangled),
+                        name_span,
+                    )),
+                    args: call_args,
+                },
+                span,
+            ))
+        });
+        // zn(string, { fn(shout, (s), { ... }) }) binds __string__shout
+        self.add_lazy_fn("zn", (2, 2), |env, mut args| {
+            let body = args.pop().context("Failed to pop body")?;
+            let namespace = args.pop().context("Failed to pop namespace")?;
+            let namespace = match namespace.kind {
+                ExpressionKind::Variable(name) => name,
+                // `none` is a keyword as well as a type name.
+                ExpressionKind::Literal(LiteralValue::None) => Value::None.type_name().to_string(),
+                _ => {
+                    return Err(
+                        Diagnostic::error("Expected a namespace name", namespace.span)
+                            .with_primary_label("not a name")
+                            .into(),
+                    )
+                }
+            };
+            let body = match body.kind {
+                ExpressionKind::Block(b) => b,
+                _ => {
+                    return Err(Diagnostic::error("Expected a block", body.span)
+                        .with_primary_label("the body of a namespace must be a `{ }` block")
+                        .into())
                 }
-                x => x,
             };
 
-            env.resolve_expression(expr)
+            let frame = env.run_scoped(body)?;
+            let frame = frame.borrow();
+            for (name, binding) in frame.locals() {
+                env.set_value(
+                    &mangle_name(&namespace, name),
+                    binding.value.clone(),
+                    binding.constant,
+                )?;
+            }
+            Ok(Value::None)
+        });
+        // infixl("<>", 6)
+        self.add_fn("infixl", 2, |env, args| {
+            set_precedence(env, args, Associativity::Left)
+        });
+        self.add_fn("infixr", 2, |env, args| {
+            set_precedence(env, args, Associativity::Right)
         });
         self.add_fn("x2", 1, |env, args| {
             let mut args = env.resolve_args(args)?;
             let value_1 = args.pop().context("Failed to pop arg1")?.get_number()?;
-            Ok(Value::Number(value_1 * 2))
+            Ok(Value::Number(value_1.mul(&Number::Int(2))))
         });
         self.add_fn("to_int", 2, |env, args| {
             let mut args = env.resolve_args(args)?;
-            let value_1 = args.pop().context("Failed to pop arg1")?.get_number()?;
+            let value_1 = args.pop().context("Failed to pop arg1")?.get_int()?;
             let value_2 = args.pop().context("Failed to pop arg2")?.get_string()?;
-            let i = u128::from_str_radix(&value_2, value_1 as u32)?;
-            Ok(Value::Number(i))
+            let radix = u32::try_from(value_1).context("Radix out of range")?;
+            Ok(Value::Number(Number::parse_radix(&value_2, radix)?))
         });
         self.add_fn("__get_symbol_bind__", 1, |env, args| {
             let mut args = env.resolve_args(args)?;
@@ -101,33 +386,130 @@ impl XurVM {
 
             let binding = env
                 .get_value(value_1.as_ref())
-                .context(format!("Failed to get binding for \"{}\"", &value_1))?
-                .clone();
+                .context(format!("Failed to get binding for \"{}\"", &value_1))?;
 
             let fn_ref = binding.get_callable().context(format!(
                 "Faiiled to convert \"{}\" bind to callable.",
                 &value_1
             ))?;
 
-            return Ok(Value::Function(fn_ref));
-            Ok(Value::create_fn(
-                &format!("__sym_bind_@{}", value_1),
-                1,
-                |env, args| {
-                    let mut args = env.resolve_args(args)?;
-                    let value_1 = args.pop().context("Failed to pop arg1")?.get_string()?;
-                    println!("get fn {}", value_1);
-                    Ok(Value::Number(10))
+            Ok(Value::Function(fn_ref))
+        });
+
+        // fn(name, (params), { body })
+        self.add_lazy_fn("fn", (2, usize::MAX), |env, mut args| {
+            let body = args.pop().context("Failed to pop body")?;
+            let body = match body.kind {
+                ExpressionKind::Block(b) => b,
+                _ => {
+                    return Err(Diagnostic::error("Expected a block", body.span)
+                        .with_primary_label("the body of a function must be a `{ }` block")
+                        .into())
+                }
+            };
+
+            let mut args = args
+                .into_iter()
+                .flat_map(|a| match a.kind {
+                    ExpressionKind::ParenList(params) => params,
+                    _ => vec![a],
+                })
+                .filter(|a| !a.is_none());
+            let name = match args.next() {
+                Some(Expression {
+                    kind: ExpressionKind::Variable(name),
+                    ..
+                }) => name,
+                Some(e) => {
+                    return Err(Diagnostic::error("Expected a function name", e.span)
+                        .with_primary_label("not a name")
+                        .into())
+                }
+                None => bail!("Expected a function name"),
+            };
+            let params = args
+                .map(|a| match a.kind {
+                    ExpressionKind::Variable(p) => Ok(p),
+                    _ => Err(Diagnostic::error("Expected a parameter name", a.span)
+                        .with_primary_label("not a name")
+                        .into()),
+                })
+                .collect::<anyhow::Result<Vec<String>>>()?;
+
+            let scope = env.current_scope();
+            let f = Value::Function(Callable::lambda(
+                &name,
+                Lambda {
+                    params,
+                    body,
+                    scope,
                 },
-            ))
+            ));
+            env.define_value(&name, f.clone())?;
+            Ok(f)
         });
 
-        self.add_fn("map", 1, |env, args| {
-            let mut args = env.resolve_args(args)?;
-            let value_1 = args.pop().context("Failed to pop arg1")?.get_number()?;
-            Ok(Value::Number(value_1 * 2))
+        // if(cond, { then }, { else })
+        // Conditionals are lazy so only the branch that is taken gets evaluated.
+        self.add_lazy_fn("if", (2, 3), |env, args| {
+            let mut args = args.into_iter().filter(|a| !a.is_none());
+            let condition = args.next().context("Failed to pop condition")?;
+            let then = args.next().context("Failed to pop then")?;
+            if env.resolve_expression(condition)?.is_truthy() {
+                env.resolve_expression(then)
+            } else {
+                args.next()
+                    .map_or(Ok(Value::None), |e| env.resolve_expression(e))
+            }
+        });
+        // cond(x < 0, { "negative" }, x == 0, { "zero" }, { "positive" })
+        // Condition and value pairs, with an optional default at the end.
+        self.add_lazy_fn("cond", (2, usize::MAX), |env, args| {
+            let mut args = args.into_iter().filter(|a| !a.is_none());
+            while let Some(condition) = args.next() {
+                let value = match args.next() {
+                    Some(value) => value,
+                    None => return env.resolve_expression(condition),
+                };
+                if env.resolve_expression(condition)?.is_truthy() {
+                    return env.resolve_expression(value);
+                }
+            }
+            Ok(Value::None)
         });
-        //
+        // match(x, 1, { "one" }, >(,1), { "many" }, [a, ..], { a }, { "none" })
+        // Pattern and value pairs, with an optional default at the end. A pattern
+        // matches if it is equal to `x`, or if it is a function that returns true for it.
+        // Array patterns and `_` destructure instead, binding names for the value.
+        self.add_lazy_fn("match", (3, usize::MAX), |env, args| {
+            let mut args = args.into_iter().filter(|a| !a.is_none());
+            let value = args.next().context("Failed to pop value")?;
+            let value = env.resolve_expression(value)?;
+            while let Some(pattern) = args.next() {
+                let body = match args.next() {
+                    Some(body) => body,
+                    None => return env.resolve_expression(pattern),
+                };
+                if is_destructuring(&pattern) {
+                    let pattern = Pattern::from_expression(&pattern)?;
+                    // Anything that doesn't destructure just moves on to the next arm.
+                    if let Ok(bindings) = destructure(&pattern, value.clone()) {
+                        return env.resolve_with_bindings(bindings, body);
+                    }
+                    continue;
+                }
+                let matched = match env.resolve_expression(pattern)? {
+                    Value::Function(f) => f.call_values(env, vec![value.clone()])?.is_truthy(),
+                    pattern => pattern == value,
+                };
+                if matched {
+                    return env.resolve_expression(body);
+                }
+            }
+            Ok(Value::None)
+        });
+
+        builtins::register(&mut self);
         self
     }
 
@@ -135,47 +517,149 @@ impl XurVM {
     pub fn execute(&mut self, s: Statement) -> anyhow::Result<Value> {
         debug!("executing");
         match s {
-            Statement::Expr(e) => self.run_expr(e),
-            Statement::Empty => Ok(Value::None),
-            x => bail!("Statement {:?} not supported", x),
+            Statement::Expr(e) => self.get_value(e),
+            Statement::Assign {
+                target,
+                value,
+                kind,
+                ..
+            } => {
+                let value = self.get_value(value)?;
+                for (name, span, v) in destructure(&target, value.clone())? {
+                    if kind == AssignKind::Nonlocal {
+                        if Enviroment::get_value(self, &name).is_none() {
+                            return Err(Diagnostic::error(
+                                format!("\"{}\" is not defined.", name),
+                                span,
+                            )
+                            .with_primary_label("`nonlocal` needs an existing binding")
+                            .into());
+                        }
+                        Scope::assign_nonlocal(&self.scope, &name, v)
+                    } else {
+                        let constant = kind == AssignKind::Const;
+                        Enviroment::set_value(self, &name, v, constant)
+                    }
+                    .map_err(|e| {
+                        Diagnostic::error(e.to_string(), span)
+                            .with_primary_label("cannot assign twice")
+                            .with_note(format!("\"{}\" was declared with `const`", name))
+                    })?;
+                }
+                Ok(value)
+            }
+            Statement::Empty(_) => Ok(Value::None),
         }
     }
 
     #[tracing::instrument(skip(self, ex))]
     fn get_value(&mut self, ex: Expression) -> anyhow::Result<Value> {
         debug!("get_value {}", ex.short_name());
-        match ex {
-            Expression::Variable(s) => {
-                let v = self
-                    .global_state
-                    .get(&s)
-                    .context(format!("\"{}\" is not defined.", s))?;
-                Ok(v.clone())
+        match ex.kind {
+            ExpressionKind::Variable(s) => {
+                let v = Enviroment::get_value(self, &s).ok_or_else(|| {
+                    Diagnostic::error(format!("\"{}\" is not defined.", s), ex.span)
+                        .with_primary_label("not found in this scope")
+                })?;
+                Ok(v)
+            }
+            ExpressionKind::Literal(s) => Ok(Value::from(s)),
+            ExpressionKind::Block(statements) => {
+                let frame = Scope::new_ref(Some(self.scope.clone()));
+                self.run_in_scope(frame, statements)
+            }
+            ExpressionKind::Value(v) => Ok(v),
+            ExpressionKind::Array(a) => {
+                let mut values = Vec::new();
+                for e in a {
+                    match e.kind {
+                        // [0, ..xs]
+                        ExpressionKind::Spread(inner) if !inner.is_none() => {
+                            values.extend(self.get_value(*inner)?.get_array()?)
+                        }
+                        _ => values.push(self.get_value(e)?),
+                    }
+                }
+                Ok(Value::Array(values))
             }
-            Expression::Literal(s) => match s {
-                LiteralValue::Number(n) => Ok(Value::Number(n)),
-                LiteralValue::String(s) => Ok(Value::String(s)),
-                _ => bail!("Value type {:?} not supported yet", s),
-            },
-            Expression::Array(a) => {
+            ExpressionKind::Format(parts) => {
+                let mut s = String::new();
+                for part in parts {
+                    s.push_str(&self.get_value(part)?.display().to_string());
+                }
+                Ok(Value::String(s.into()))
+            }
+            ExpressionKind::Tuple(items) => {
                 let values: anyhow::Result<Vec<Value>> =
-                    a.into_iter().map(|e| self.get_value(e)).collect();
-                values.map(|v| Value::Array(v))
+                    items.into_iter().map(|e| self.get_value(e)).collect();
+                values.map(Value::Tuple)
+            }
+            // (1 + 2)
+            ExpressionKind::ParenList(items) if items.len() == 1 && !items[0].is_none() => {
+                self.get_value(items.into_iter().next().expect("one item"))
             }
-            x => self.run_expr(x),
+            ExpressionKind::ParenList(_) => Err(Diagnostic::error(
+                "A parameter set can only be used as arguments",
+                ex.span,
+            )
+            .with_primary_label("not passed to a function")
+            .with_note("use `@(...)` for a tuple")
+            .into()),
+            ExpressionKind::Map(entries) => {
+                let mut map = Map::new();
+                for (key, value) in entries {
+                    let key_span = key.span;
+                    let key = self.get_value(key)?;
+                    let value = self.get_value(value)?;
+                    map.insert(key, value)
+                        .map_err(|e| Diagnostic::attach(e, key_span))?;
+                }
+                Ok(Value::Map(map))
+            }
+            ExpressionKind::Spread(_) => Err(Diagnostic::error(
+                "`..` can only spread a value into an array",
+                ex.span,
+            )
+            .with_primary_label("not inside an array")
+            .into()),
+            _ => self.run_expr(ex),
         }
     }
 
     #[tracing::instrument(skip(self, ex))]
     fn run_expr(&mut self, ex: Expression) -> anyhow::Result<Value> {
         debug!("run_expr {}", ex.short_name());
-        match ex {
-            Expression::Call { callie, args } => {
-                let mut fn_value = self.get_value(*callie)?.get_callable()?;
+        let span = ex.span;
+        match ex.kind {
+            ExpressionKind::Call { callie, args } => {
+                let callie_span = callie.span;
+                let fn_value = self
+                    .get_value(*callie)?
+                    .get_callable()
+                    .map_err(|e| Diagnostic::attach(e, callie_span))?;
+
+                fn_value
+                    .call(self, args)
+                    .map_err(|e| Diagnostic::attach(e, span))
+            }
+            kind => bail!("Cant run expression {:?}", kind),
+        }
+    }
 
-                fn_value.call(self, args)
+    fn run_in_scope(
+        &mut self,
+        scope: ScopeRef,
+        statements: Vec<Statement>,
+    ) -> anyhow::Result<Value> {
+        let outer = std::mem::replace(&mut self.scope, scope);
+        let mut last = Ok(Value::None);
+        for statement in statements {
+            last = self.execute(statement);
+            if last.is_err() {
+                break;
             }
-            x => bail!("Cant run expression {:?}", x),
         }
+        self.scope = outer;
+        last
     }
 }
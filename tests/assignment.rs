@@ -0,0 +1,52 @@
+mod common;
+
+use common::{eval_lines, repl};
+
+#[test]
+fn assignment_returns_the_value() {
+    assert_eq!(eval_lines("x = 1 + 2\n"), ["3"]);
+}
+
+#[test]
+fn bindings_persist_across_lines() {
+    let out = eval_lines(
+        r#"hex = ["ff", "10"]
+base = 16
+to_int("ff", base) + 1
+hex
+"#,
+    );
+    assert_eq!(out, ["[\"ff\", \"10\"]", "16", "256", "[\"ff\", \"10\"]"]);
+}
+
+#[test]
+fn variables_can_be_rebound() {
+    assert_eq!(eval_lines("x = 1\nx = x + 1\nx\n"), ["1", "2", "2"]);
+}
+
+#[test]
+fn functions_can_be_bound() {
+    assert_eq!(
+        eval_lines("hex = to_int(,16)\nhex(\"ff\")\n"),
+        ["<fn to_int>", "255"]
+    );
+}
+
+#[test]
+fn constants_cannot_be_rebound() {
+    let out = repl("const x = 1\nx = 2\nconst x = 3\nx\n");
+    assert_eq!(out.stdout, "1\n1\n");
+    assert_eq!(
+        out.stderr
+            .matches("Cannot assign twice to constant \"x\"")
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn only_names_can_be_assigned() {
+    let out = repl("1 = 2\n");
+    assert!(out.stdout.is_empty());
+    assert!(out.stderr.contains("Invalid assignment target"));
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use common::{eval, eval_lines, repl};
+
+#[test]
+fn keywords_are_values() {
+    assert_eq!(eval("[true, false, none]"), "[true, false, none]");
+    assert_eq!(eval("1 < 2"), "true");
+}
+
+#[test]
+fn none_is_not_printed() {
+    assert_eq!(eval_lines("none\n1\n"), ["1"]);
+}
+
+#[test]
+fn truthiness() {
+    assert_eq!(
+        eval(r#"[true, false, 1, 0, 0.0, "a", "", [0], [], none].map(!)"#),
+        "[false, true, false, true, true, false, true, false, true, true]"
+    );
+    assert_eq!(eval("[1, 2, 3].map(>(,1)).filter(!)"), "[false]");
+}
+
+#[test]
+fn bools_compare() {
+    assert_eq!(eval("true == true"), "true");
+    assert_eq!(eval("true == 1"), "false");
+    assert_eq!(eval("false < true"), "true");
+}
+
+#[test]
+fn keywords_cannot_be_assigned() {
+    let out = repl("true = 1\n");
+    assert!(
+        out.stderr.contains("Invalid assignment target"),
+        "{}",
+        out.stderr
+    );
+}
+
+#[test]
+fn bool_namespace() {
+    let out = eval_lines(
+        "zn(bool, { fn(flip, (b), { !(b) }) })\n\
+         true->flip\n",
+    );
+    assert_eq!(out, ["false"]);
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::{eval, eval_lines, repl};
+
+#[test]
+fn map_with_a_partial_function() {
+    assert_eq!(
+        eval(r#"["AA", "BB", "CC"].map(to_int(,16))"#),
+        "[170, 187, 204]"
+    );
+}
+
+#[test]
+fn map_over_a_string() {
+    assert_eq!(
+        eval(r#""ab".map(fn(pair, (c), { [c, c] }))"#),
+        r#"[["a", "a"], ["b", "b"]]"#
+    );
+}
+
+#[test]
+fn map_with_a_user_function() {
+    let out = eval_lines("fn(inc, (x), { x + 1 })\n[1, 2].map(inc)\n");
+    assert_eq!(out[1], "[2, 3]");
+}
+
+#[test]
+fn filter_keeps_truthy_results() {
+    assert_eq!(eval("[0, 1, 2, 0].filter(x2)"), "[1, 2]");
+    assert_eq!(eval(r#"["", "a"].filter(len)"#), r#"["a"]"#);
+}
+
+#[test]
+fn filter_over_a_string_gives_a_string() {
+    assert_eq!(eval(r#""0a0b".filter(to_int(,16))"#), "\"ab\"");
+}
+
+#[test]
+fn reduce_and_fold() {
+    assert_eq!(eval("[1, 2, 3].reduce(+)"), "6");
+    assert_eq!(eval("[1, 2, 3].fold(10, +)"), "16");
+    assert_eq!(eval("[].fold(10, +)"), "10");
+}
+
+#[test]
+fn reduce_of_nothing_is_an_error() {
+    let out = repl("[].reduce(+)\n");
+    assert!(out.stderr.contains("Cannot reduce an empty collection"));
+}
+
+#[test]
+fn flat_map_flattens_one_level() {
+    assert_eq!(
+        eval("[1, 2].flat_map(fn(twice, (x), { [x, [x]] }))"),
+        "[1, [1], 2, [2]]"
+    );
+}
+
+#[test]
+fn zip_and_enumerate() {
+    assert_eq!(eval(r#"zip([1, 2, 3], "ab")"#), r#"[[1, "a"], [2, "b"]]"#);
+    assert_eq!(eval(r#""ab".enumerate"#), r#"[[0, "a"], [1, "b"]]"#);
+}
+
+#[test]
+fn any_and_all() {
+    assert_eq!(eval("[0, 0, 1].any(x2)"), "true");
+    assert_eq!(eval("[0, 0].any(x2)"), "false");
+    assert_eq!(eval("[1, 2].all(x2)"), "true");
+    assert_eq!(eval("[1, 0].all(x2)"), "false");
+}
+
+#[test]
+fn sum_count_and_len() {
+    assert_eq!(eval("[1, 2, 3].sum"), "6");
+    assert_eq!(eval("[0, 1, 2].count(x2)"), "2");
+    assert_eq!(eval(r#""héllo".len"#), "5");
+    assert_eq!(eval("[[1], 2].len"), "2");
+}
+
+#[test]
+fn array_literals() {
+    assert_eq!(eval("[]"), "[]");
+    assert_eq!(eval("[1,]"), "[1]");
+}
@@ -0,0 +1,73 @@
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct Output {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Pipes `input` into the xur repl and collects everything it printed.
+pub fn repl(input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_xur"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to start xur");
+
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(input.as_bytes())
+        .expect("failed to write to xur stdin");
+
+    let output = child.wait_with_output().expect("failed to wait on xur");
+    assert!(output.status.success(), "xur exited with {}", output.status);
+    Output {
+        stdout: String::from_utf8(output.stdout).expect("stdout is utf8"),
+        stderr: String::from_utf8(output.stderr).expect("stderr is utf8"),
+    }
+}
+
+/// Writes `source` to a file and runs it as a script, like `xur file.xur`.
+pub fn script(source: &str) -> Output {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "xur-script-{}-{}.xur",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::write(&path, source).expect("failed to write script");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_xur"))
+        .arg(&path)
+        .output()
+        .expect("failed to run xur");
+    std::fs::remove_file(&path).ok();
+    Output {
+        stdout: String::from_utf8(output.stdout).expect("stdout is utf8"),
+        stderr: String::from_utf8(output.stderr).expect("stderr is utf8"),
+    }
+}
+
+/// Runs each line through the repl and returns the printed results, one per line.
+pub fn eval_lines(input: &str) -> Vec<String> {
+    repl(input).stdout.lines().map(String::from).collect()
+}
+
+/// Evaluates a single line and returns the one result it printed.
+pub fn eval(line: &str) -> String {
+    let out = eval_lines(&format!("{}\n", line));
+    assert_eq!(
+        out.len(),
+        1,
+        "expected one result from {:?}, got {:?}",
+        line,
+        out
+    );
+    out.into_iter().next().unwrap()
+}
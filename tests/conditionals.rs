@@ -0,0 +1,70 @@
+mod common;
+
+use common::{eval, eval_lines, repl};
+
+#[test]
+fn if_takes_one_branch() {
+    assert_eq!(eval(r#"if(1 < 2, { "yes" }, { "no" })"#), r#""yes""#);
+    assert_eq!(eval(r#"if(none, "yes", "no")"#), r#""no""#);
+}
+
+#[test]
+fn if_without_else() {
+    assert_eq!(eval_lines("if(false, { 1 })\n2\n"), ["2"]);
+}
+
+#[test]
+fn untaken_branches_are_not_evaluated() {
+    let out = repl("if(true, 1, nope)\nif(false, nope, 2)\n");
+    assert_eq!(out.stdout, "1\n2\n");
+    assert_eq!(out.stderr, "");
+}
+
+#[test]
+fn if_is_partially_applicable() {
+    assert_eq!(eval(r#"[1, 0].map(if(, "y", "n"))"#), r#"["y", "n"]"#);
+}
+
+#[test]
+fn recursion() {
+    let out = eval_lines(
+        "fn(fact, (n), { if(n <= 1, 1, { n * fact(n - 1) }) })\n\
+         fact(25)\n",
+    );
+    assert_eq!(out[1], "15511210043330985984000000");
+}
+
+#[test]
+fn cond_picks_the_first_true_arm() {
+    let out = eval_lines(
+        "fn(sign, (x), {\n\
+             cond(\n\
+                 x < 0, { \"negative\" },\n\
+                 x == 0, { \"zero\" },\n\
+                 { \"positive\" }\n\
+             )\n\
+         })\n\
+         [-5, 0, 5].map(sign)\n",
+    );
+    assert_eq!(out[1], r#"["negative", "zero", "positive"]"#);
+}
+
+#[test]
+fn cond_without_a_default() {
+    assert_eq!(eval_lines("cond(false, 1)\n2\n"), ["2"]);
+    assert_eq!(eval("cond(false, nope, true, 2)"), "2");
+}
+
+#[test]
+fn match_values_and_predicates() {
+    let out = eval_lines(
+        "fn(size, (n), { match(n, 0, \"none\", 1, \"one\", >(,100), \"lots\", \"some\") })\n\
+         [0, 1, 5, 500].map(size)\n",
+    );
+    assert_eq!(out[1], r#"["none", "one", "some", "lots"]"#);
+}
+
+#[test]
+fn match_as_a_method() {
+    assert_eq!(eval(r#""b".match("a", { 1 }, "b", { 2 })"#), "2");
+}
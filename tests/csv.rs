@@ -0,0 +1,121 @@
+mod common;
+
+use common::{eval, eval_lines, repl};
+
+const PEOPLE: &str = r#"people = "name,age\nbob,31\n\"smith, al\",\"4\"\"2\"\n""#;
+
+#[test]
+fn parse_with_a_header() {
+    let out = eval_lines(&format!("{}\npeople.csv_parse\n", PEOPLE));
+    assert_eq!(
+        out[1],
+        r#"[{"name": "bob", "age": "31"}, {"name": "smith, al", "age": "4\"2"}]"#
+    );
+}
+
+#[test]
+fn header_detection() {
+    assert_eq!(
+        eval(r#""1,2\n3,4".csv_parse"#),
+        r#"[["1", "2"], ["3", "4"]]"#
+    );
+    assert_eq!(
+        eval(r#""a,a\nx,y".csv_parse"#),
+        r#"[["a", "a"], ["x", "y"]]"#
+    );
+    assert_eq!(
+        eval(r#"csv_parse("a,b\nx,y", {"header": false})"#),
+        r#"[["a", "b"], ["x", "y"]]"#
+    );
+    assert_eq!(
+        eval(r#"csv_parse("1,2\n3,4", {"header": true})"#),
+        r#"[{"1": "3", "2": "4"}]"#
+    );
+}
+
+#[test]
+fn delimiters_quotes_and_numbers() {
+    assert_eq!(
+        eval(r#""a\tb\nx\ty".csv_parse"#),
+        r#"[{"a": "x", "b": "y"}]"#
+    );
+    assert_eq!(
+        eval(r#""a;b\n1;2.5".csv_parse({"delimiter": ";", "numbers": true})"#),
+        r#"[{"a": 1, "b": 2.5}]"#
+    );
+    assert_eq!(
+        eval(r#""a\n'x,y'".csv_parse({"quote": "'"})"#),
+        r#"[{"a": "x,y"}]"#
+    );
+    assert_eq!(
+        eval(r#""a,b\n\"x\\\"y\",2".csv_parse({"escape": "\\"})"#),
+        r#"[{"a": "x\"y", "b": "2"}]"#
+    );
+}
+
+#[test]
+fn dump_maps_and_arrays() {
+    assert_eq!(
+        eval(r#"[[1, "a,b"], [none, "q\""]].csv_dump"#),
+        r#""1,\"a,b\"\n,\"q\"\"\"\n""#
+    );
+    assert_eq!(
+        eval(r#"[{"a": 1}, {"b": 2}].csv_dump({"delimiter": "\t"})"#),
+        r#""a\tb\n1\t\n\t2\n""#
+    );
+    assert_eq!(
+        eval(r#"[{"a": 1}].csv_dump({"header": false})"#),
+        r#""1\n""#
+    );
+}
+
+#[test]
+fn round_trip() {
+    let out = eval_lines(&format!(
+        "{}\npeople.csv_parse.csv_dump == people\n",
+        PEOPLE
+    ));
+    assert_eq!(out[1], "true");
+}
+
+#[test]
+fn composes_on_one_line() {
+    let out = eval_lines(&format!(
+        "{}\npeople.csv_parse.map(get(,\"name\")).filter(contains(,\" \"))\n",
+        PEOPLE
+    ));
+    assert_eq!(out[1], r#"["smith, al"]"#);
+    assert_eq!(
+        eval(r#""n\n1\n5\n9".csv_parse({"numbers": true}).map(get(,"n")).filter(>(,4)).map(x2)"#),
+        "[10, 18]"
+    );
+}
+
+#[test]
+fn errors() {
+    let out = repl(concat!(
+        r#""a".csv_parse({"bogus": 1})"#,
+        "\n",
+        r#""a,b\n1,2,3".csv_parse"#,
+        "\n",
+        r#""a".csv_parse({"delimiter": "ab"})"#,
+        "\n",
+    ));
+    assert!(out.stdout.is_empty());
+    assert!(
+        out.stderr.contains(r#"Unknown csv option "bogus""#),
+        "{}",
+        out.stderr
+    );
+    assert!(
+        out.stderr.contains("found record with 3 fields"),
+        "{}",
+        out.stderr
+    );
+    assert!(
+        out.stderr
+            .contains(r#"The delimiter must be one ascii character, got "ab""#),
+        "{}",
+        out.stderr
+    );
+}
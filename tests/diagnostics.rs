@@ -0,0 +1,194 @@
+mod common;
+
+use std::io::Write;
+use std::process::Command;
+
+use xur::diagnostic::{Diagnostic, Renderer, Source};
+use xur::lexer::Span;
+use xur::repl::Repl;
+
+fn render_error(line: &str) -> String {
+    let mut repl = Repl::new();
+    let err = repl.eval(line).expect_err("line should fail");
+    repl.report(&err)
+}
+
+#[test]
+fn undefined_variable() {
+    assert_eq!(
+        render_error("1 + nope"),
+        r#"error: "nope" is not defined.
+ --> <repl>:1:5
+  |
+1 | 1 + nope
+  |     ^^^^ not found in this scope
+  |
+  = note: while calling "+"
+"#
+    );
+}
+
+#[test]
+fn failing_call_notes_the_callee() {
+    assert_eq!(
+        render_error(r#"to_int("zz", 16)"#),
+        r#"error: "zz" is not a base 16 integer
+ --> <repl>:1:1
+  |
+1 | to_int("zz", 16)
+  | ^^^^^^^^^^^^^^^^
+  |
+  = note: while calling "to_int"
+"#
+    );
+}
+
+#[test]
+fn unclosed_list_labels_the_opener() {
+    assert_eq!(
+        render_error("[1, 2"),
+        r#"error: Unexpected end of list
+ --> <repl>:1:6
+  |
+1 | [1, 2
+  | - list starts here
+  |      ^ expected `]`
+"#
+    );
+}
+
+#[test]
+fn labels_on_several_lines() {
+    let text = "a(\n  b,\n  c";
+    let diagnostic = Diagnostic::warning(
+        "something odd",
+        Span {
+            start: 10,
+            end: 11,
+            line: 3,
+            col: 3,
+        },
+    )
+    .with_primary_label("here")
+    .with_label(
+        Span {
+            start: 0,
+            end: 1,
+            line: 1,
+            col: 1,
+        },
+        "called here",
+    )
+    .with_note("just a test");
+
+    assert_eq!(
+        Renderer::plain().render(&diagnostic, &Source::new("test.xur", text)),
+        r#"warning: something odd
+ --> test.xur:3:3
+  |
+1 | a(
+  | - called here
+3 |   c
+  |   ^ here
+  |
+  = note: just a test
+"#
+    );
+}
+
+#[test]
+fn spans_outside_the_source_are_left_out() {
+    let diagnostic = Diagnostic::error(
+        "from somewhere else",
+        Span {
+            start: 20,
+            end: 21,
+            line: 1,
+            col: 3,
+        },
+    )
+    .with_primary_label("here");
+
+    assert_eq!(
+        Renderer::plain().render(&diagnostic, &Source::new("test.xur", "a(b)")),
+        "error: from somewhere else\n --> test.xur\n"
+    );
+    assert_eq!(
+        Renderer::plain().render(
+            &diagnostic,
+            &Source::new("test.xur", "a(b)").with_offset(18)
+        ),
+        "error: from somewhere else\n --> test.xur:1:3\n  |\n1 | a(b)\n  |   ^ here\n"
+    );
+}
+
+#[test]
+fn errors_in_functions_point_at_their_definition() {
+    let mut repl = Repl::new();
+    repl.eval("fn(f, (n), { n to_int 16 })").unwrap();
+    repl.eval("x = 1").unwrap();
+    let err = repl.eval("f(2)").expect_err("f(2) should fail");
+    assert_eq!(
+        repl.report(&err),
+        r#"error: 2 is not a string
+ --> <repl>:1:14
+  |
+1 | fn(f, (n), { n to_int 16 })
+  |              ^^^^^^^^^^^
+  |
+  = note: while calling "to_int"
+  = note: while calling "f"
+"#
+    );
+    let err = repl.eval("nope").expect_err("nope should fail");
+    assert!(repl
+        .report(&err)
+        .contains(" --> <repl>:1:1\n  |\n1 | nope\n"));
+}
+
+#[test]
+fn repl_prints_diagnostics_to_stderr() {
+    let out = common::repl("nope\n");
+    assert!(out.stdout.is_empty());
+    assert!(out
+        .stderr
+        .contains("1 | nope\n  | ^^^^ not found in this scope"));
+}
+
+#[test]
+fn script_errors_point_into_the_file() {
+    let path = std::env::temp_dir().join(format!("xur-diagnostics-{}.xur", std::process::id()));
+    std::fs::File::create(&path)
+        .and_then(|mut f| f.write_all(b"1 + 1;\n\nto_int(\"zz\", 16)\n"))
+        .expect("failed to write script");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_xur"))
+        .arg(&path)
+        .output()
+        .expect("failed to run xur");
+    std::fs::remove_file(&path).ok();
+
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "2\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&format!("--> {}:3:1", path.display())));
+    assert!(stderr.contains("3 | to_int(\"zz\", 16)\n  | ^^^^^^^^^^^^^^^^"));
+}
+
+#[test]
+fn unterminated_string() {
+    assert_eq!(
+        render_error("x = \"abc"),
+        r#"error: Unterminated string
+ --> <repl>:1:5
+  |
+1 | x = "abc
+  |     ^^^^ expected a closing `"`
+"#
+    );
+}
+
+#[test]
+fn unknown_escape() {
+    assert!(render_error(r#""a\qb""#).contains("error: Unknown escape `\\q`"));
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use common::{eval_lines, repl};
+
+#[test]
+fn readme_examples() {
+    let out = eval_lines(
+        r#"to_int("FF", 16)
+"FF" to_int 16
+to_int(,16)("FF")
+"FF".to_int(16)
+(,16).to_int("FF")
+"#,
+    );
+    assert_eq!(out, ["255"; 5]);
+}
+
+#[test]
+fn bare_identifiers() {
+    assert_eq!(eval_lines("x = 5\nx.x2\n"), ["5", "10"]);
+}
+
+#[test]
+fn receiver_fills_the_first_hole() {
+    assert_eq!(eval_lines("\"FF\".to_int(,16)\n"), ["255"]);
+}
+
+#[test]
+fn partial_callables() {
+    let out = eval_lines(
+        r#"hex = to_int(,16)
+"FF".hex
+fn(add, (a, b), { a + b })
+add1 = add(1)
+2.add1
+"#,
+    );
+    assert_eq!(out, ["<fn to_int>", "255", "<fn add>", "<fn add>", "3"]);
+}
+
+#[test]
+fn paren_list_receivers() {
+    let out = eval_lines(
+        r#"("FF", 16).to_int
+(,16).to_int("FF")
+("FF",).to_int(16)
+"#,
+    );
+    assert_eq!(out, ["255", "255", "255"]);
+}
+
+#[test]
+fn chained_calls() {
+    let out = eval_lines(
+        r#""ff".to_int(16).x2
+5.x2.x2.x2
+"1".to_int(10).x2 + 1
+"#,
+    );
+    assert_eq!(out, ["510", "40", "3"]);
+}
+
+#[test]
+fn receiver_is_evaluated_once() {
+    let out = eval_lines(
+        r#"n = 0
+fn(next, (), { nonlocal n = n + 1 })
+next().x2
+n
+"#,
+    );
+    assert_eq!(&out[2..], ["2", "1"]);
+}
+
+#[test]
+fn arrow_chains_like_dot() {
+    let out = eval_lines(
+        r#"zn(string, { fn(hex, (s), { to_int(s, 16) }) })
+zn(number, { fn(double, (n), { n + n }) })
+"ff"->hex->double
+"#,
+    );
+    assert_eq!(out, ["510"]);
+}
+
+#[test]
+fn dot_needs_a_name() {
+    let out = repl("5.\"x\"\n");
+    assert!(out.stderr.contains("Expected a function name after `.`"));
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::{eval_lines, repl};
+
+#[test]
+fn fn_binds_and_returns_the_function() {
+    let out = eval_lines("fn(add, (a, b), { a + b })\nadd(1, 2)\n");
+    assert_eq!(out, ["<fn add>", "3"]);
+}
+
+#[test]
+fn user_functions_are_partially_applicable() {
+    let out = eval_lines(
+        r#"fn(add, (a, b), { a + b })
+add(1)(2)
+add(,5)(1)
+1 add 2
+plus_ten = add(10)
+plus_ten(5)
+"#,
+    );
+    assert_eq!(out, ["<fn add>", "3", "6", "3", "<fn add>", "15"]);
+}
+
+#[test]
+fn parameter_sets_expand_into_user_functions() {
+    let out = eval_lines(
+        r#"fn(add3, (a, b, c), { a + b + c })
+add3((1, 2), 3)
+args = add3(1, 2)
+args(3)
+"#,
+    );
+    assert_eq!(out, ["<fn add3>", "6", "<fn add3>", "6"]);
+}
+
+#[test]
+fn functions_without_parameters() {
+    assert_eq!(eval_lines("fn(k, (), { 42 })\nk()\n"), ["<fn k>", "42"]);
+}
+
+#[test]
+fn functions_can_take_functions() {
+    let out = eval_lines("fn(twice, (f, v), { f(f(v)) })\ntwice(x2, 3)\n");
+    assert_eq!(out, ["<fn twice>", "12"]);
+}
+
+#[test]
+fn blocks_return_their_last_statement() {
+    assert_eq!(eval_lines("{ 1; 2 }\n"), ["2"]);
+}
+
+#[test]
+fn multi_line_definitions() {
+    let out = eval_lines(
+        r#"fn(hex_sum, (a, b), {
+    a = to_int(a, 16)
+    b = to_int(b, 16)
+    a + b
+})
+hex_sum("f", "1")
+"#,
+    );
+    assert_eq!(out, ["<fn hex_sum>", "16"]);
+}
+
+#[test]
+fn parameters_do_not_leak() {
+    let out = repl("a = 1\nfn(f, (a), { a + a })\nf(5)\na\n");
+    assert_eq!(out.stdout, "1\n<fn f>\n10\n1\n");
+}
+
+#[test]
+fn fn_needs_a_block() {
+    let out = repl("fn(f, (a), a)\n");
+    assert!(out.stderr.contains("Expected a block"));
+}
+
+#[test]
+fn fn_cannot_replace_a_constant() {
+    let out = repl("const y = 3\nfn(y, (a), { a })\ny\n");
+    assert!(
+        out.stderr.contains("Cannot assign twice to constant \"y\""),
+        "{}",
+        out.stderr
+    );
+    assert_eq!(out.stdout, "3\n3\n");
+}
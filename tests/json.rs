@@ -0,0 +1,81 @@
+mod common;
+
+use common::{eval, repl};
+
+#[test]
+fn parse_maps_onto_values() {
+    assert_eq!(
+        eval(r#"json_parse("""{"b": [1, 2.5, null, true], "a": {"x": "y"}}""")"#),
+        r#"{"b": [1, 2.5, none, true], "a": {"x": "y"}}"#
+    );
+    assert_eq!(
+        eval(r#"json_parse("18446744073709551615") + 1"#),
+        "18446744073709551616"
+    );
+}
+
+#[test]
+fn dump_compact_and_indented() {
+    assert_eq!(
+        eval(r#"json_dump({"a": [1, none], "b": @(true, 1.5)}, 0)"#),
+        r#""{\"a\":[1,null],\"b\":[true,1.5]}""#
+    );
+    assert_eq!(
+        eval(r#"json_dump({"a": [1]}, 4)"#),
+        r#""{\n    \"a\": [\n        1\n    ]\n}""#
+    );
+    assert_eq!(eval(r#"json_dump({1: "one"}, 0)"#), r#""{\"1\":\"one\"}""#);
+}
+
+#[test]
+fn round_trips() {
+    for value in [
+        r#"{"name": "xur", "tags": ["a", "b"], "n": -3, "ratio": 0.25, "ok": false, "none": none}"#,
+        r#"[[], @{}, "quote \" and \\ and \n", 9223372036854775807]"#,
+    ] {
+        assert_eq!(eval(&format!("json_parse(json_dump({}, 0))", value)), value);
+        assert_eq!(eval(&format!("json_parse(json_dump({}, 2))", value)), value);
+    }
+    let text = r#"{"z":1,"a":[true,null,"s"]}"#;
+    assert_eq!(
+        eval(&format!("json_dump(json_parse({:?}), 0)", text)),
+        format!("{:?}", text)
+    );
+}
+
+#[test]
+fn errors_report_the_path() {
+    let out = repl(concat!(
+        r#"json_parse("""{"a": {"b": [1, 2,]}}""")"#,
+        "\n",
+        r#"json_dump({"a b": [1, to_int]}, 0)"#,
+        "\n",
+        r#"json_dump([1.5, {"c": b"x"}], 0)"#,
+        "\n",
+        r#"json_parse("[1] x")"#,
+        "\n",
+    ));
+    assert!(out.stdout.is_empty());
+    assert!(
+        out.stderr.contains("Invalid JSON at $.a.b: trailing comma"),
+        "{}",
+        out.stderr
+    );
+    assert!(
+        out.stderr
+            .contains(r#"function <fn to_int> can't be written as JSON at $["a b"][1]"#),
+        "{}",
+        out.stderr
+    );
+    assert!(
+        out.stderr
+            .contains(r#"bytes b"x" can't be written as JSON at $[1].c"#),
+        "{}",
+        out.stderr
+    );
+    assert!(
+        out.stderr.contains("Invalid JSON: trailing characters"),
+        "{}",
+        out.stderr
+    );
+}
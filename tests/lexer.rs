@@ -0,0 +1,233 @@
+use xur::lexer::{FormatPart, Span, TokenKind, Tokenizer};
+
+fn kinds(source: &str) -> Vec<TokenKind> {
+    Tokenizer::new(source)
+        .map(|t| t.kind)
+        .filter(|k| !matches!(k, TokenKind::Whitespace))
+        .collect()
+}
+
+fn ident(s: &str) -> TokenKind {
+    TokenKind::Identifier(s.into())
+}
+
+#[test]
+fn arrow_is_one_identifier() {
+    assert_eq!(
+        kinds("\"ff\"->to_int"),
+        [TokenKind::String("ff".into()), ident("->"), ident("to_int")]
+    );
+}
+
+#[test]
+fn identifiers_can_start_with_underscores() {
+    assert_eq!(
+        kinds("__string__hex _"),
+        [ident("__string__hex"), ident("_")]
+    );
+}
+
+#[test]
+fn numeric_literals() {
+    assert_eq!(
+        kinds("12 0xff 0b101 0o17 1.5 1e3 2.5E-1"),
+        [
+            TokenKind::Numeric(12.into()),
+            TokenKind::Numeric(255.into()),
+            TokenKind::Numeric(5.into()),
+            TokenKind::Numeric(15.into()),
+            TokenKind::Float(1.5),
+            TokenKind::Float(1000.0),
+            TokenKind::Float(0.25),
+        ]
+    );
+}
+
+#[test]
+fn dot_after_a_number_is_a_method_call() {
+    assert_eq!(
+        kinds("5.x2"),
+        [TokenKind::Numeric(5.into()), ident("."), ident("x2")]
+    );
+}
+
+#[test]
+fn operators_are_identifiers() {
+    assert_eq!(
+        kinds("- * / ** // mod"),
+        ["-", "*", "/", "**", "//", "mod"].map(ident)
+    );
+    assert_eq!(
+        kinds("== != < <= > >= && || ! & | ^ << >>"),
+        ["==", "!=", "<", "<=", ">", ">=", "&&", "||", "!", "&", "|", "^", "<<", ">>"].map(ident)
+    );
+}
+
+#[test]
+fn operators_need_no_spaces() {
+    assert_eq!(
+        kinds("a<=b**2"),
+        [
+            ident("a"),
+            ident("<="),
+            ident("b"),
+            ident("**"),
+            TokenKind::Numeric(2.into())
+        ]
+    );
+    assert_eq!(kinds("1<<<2")[1..3], [ident("<<"), ident("<")]);
+}
+
+#[test]
+fn equals_is_not_an_operator() {
+    assert_eq!(
+        kinds("a = b == c"),
+        [
+            ident("a"),
+            TokenKind::Equals,
+            ident("b"),
+            ident("=="),
+            ident("c")
+        ]
+    );
+}
+
+#[test]
+fn literal_keywords() {
+    assert_eq!(
+        kinds("true false none truest"),
+        [
+            TokenKind::Boolean(true),
+            TokenKind::Boolean(false),
+            TokenKind::None,
+            ident("truest")
+        ]
+    );
+}
+
+#[test]
+fn dot_dot_is_not_two_method_calls() {
+    assert_eq!(
+        kinds("[a, ..rest]"),
+        [
+            TokenKind::OpenBracket,
+            ident("a"),
+            TokenKind::Comma,
+            TokenKind::DotDot,
+            ident("rest"),
+            TokenKind::CloseBracket
+        ]
+    );
+}
+
+#[test]
+fn byte_string_literals() {
+    assert_eq!(
+        kinds(r#"b"a\x00\xff\n" b"""#),
+        [
+            TokenKind::Bytes(vec![b'a', 0, 0xff, b'\n']),
+            TokenKind::Bytes(vec![])
+        ]
+    );
+    assert_eq!(kinds(r#"b"\q""#), [TokenKind::Unknown(r"\q".into())]);
+    assert_eq!(kinds("b x"), [ident("b"), ident("x")]);
+}
+
+#[test]
+fn comments() {
+    assert_eq!(
+        kinds("1 # one\n#[ a #[ b ]# c ]#2"),
+        [
+            TokenKind::Numeric(1.into()),
+            TokenKind::Comment(" one".into()),
+            TokenKind::Newline,
+            TokenKind::Comment(" a #[ b ]# c ".into()),
+            TokenKind::Numeric(2.into()),
+        ]
+    );
+    assert_eq!(kinds("#[ open"), [TokenKind::Unknown("#[".into())]);
+}
+
+#[test]
+fn string_escapes() {
+    assert_eq!(
+        kinds(r#""a\"b" "\n\t\r\0\\" "\u{48}\u{1F600}""#),
+        [
+            TokenKind::String("a\"b".into()),
+            TokenKind::String("\n\t\r\0\\".into()),
+            TokenKind::String("H😀".into()),
+        ]
+    );
+    assert_eq!(kinds(r#""\q""#), [TokenKind::Unknown("\\q".into())]);
+    assert_eq!(kinds(r#""\u{110000}""#), [TokenKind::Unknown("\\u".into())]);
+}
+
+#[test]
+fn raw_and_multiline_strings() {
+    assert_eq!(
+        kinds(r#"r"C:\dir\n" @"a\"b""#),
+        [
+            TokenKind::String("C:\\dir\\n".into()),
+            TokenKind::Symbol("a\"b".into()),
+        ]
+    );
+    assert_eq!(
+        kinds("\"\"\"\none \"two\"\n\\tthree\"\"\" r\"\"\"\\n\"\"\""),
+        [
+            TokenKind::String("one \"two\"\n\tthree".into()),
+            TokenKind::String("\\n".into()),
+        ]
+    );
+}
+
+#[test]
+fn unterminated_strings() {
+    assert_eq!(
+        kinds("\"abc\n1"),
+        [
+            TokenKind::Unknown("\"".into()),
+            TokenKind::Numeric(1.into())
+        ]
+    );
+    assert_eq!(kinds("\"\"\"abc\n"), [TokenKind::Unknown("\"\"\"".into())]);
+    assert_eq!(kinds("\"abc\\"), [TokenKind::Unknown("\"".into())]);
+}
+
+#[test]
+fn unterminated_byte_strings() {
+    assert_eq!(kinds("b\"abc"), [TokenKind::Unknown("\"".into())]);
+    assert_eq!(
+        kinds("b\"abc\n1"),
+        [
+            TokenKind::Unknown("\"".into()),
+            TokenKind::Numeric(1.into())
+        ]
+    );
+    assert_eq!(kinds("b\"abc\\"), [TokenKind::Unknown("\"".into())]);
+}
+
+#[test]
+fn format_strings() {
+    let hole = |s: &str, start: usize| {
+        FormatPart::Hole(
+            s.into(),
+            Span {
+                start,
+                end: start + s.len(),
+                line: 1,
+                col: start + 1,
+            },
+        )
+    };
+    assert_eq!(
+        kinds(r#"f"{a}: {m.get("}")} {{x}}\n""#),
+        [TokenKind::Format(vec![
+            hole("a", 3),
+            FormatPart::Text(": ".into()),
+            hole(r#"m.get("}")"#, 8),
+            FormatPart::Text(" {x}\n".into()),
+        ])]
+    );
+    assert_eq!(kinds(r#"f"{a""#), [TokenKind::Unknown("{".into())]);
+    assert_eq!(kinds(r#"f"a}""#), [TokenKind::Unknown("}".into())]);
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use common::{eval, eval_lines, repl};
+
+#[test]
+fn literals_keep_insertion_order() {
+    assert_eq!(eval(r#"{"b": 1, "a": 2}"#), r#"{"b": 1, "a": 2}"#);
+    assert_eq!(eval("{1: true, 2.5: none}"), "{1: true, 2.5: none}");
+}
+
+#[test]
+fn at_literals_take_any_key_expression() {
+    assert_eq!(eval("@{}"), "@{}");
+    assert_eq!(eval(r#"k = "x"; @{k: 1 + 1}"#), r#"{"x": 2}"#);
+}
+
+#[test]
+fn maps_display_as_literals_that_read_back() {
+    assert_eq!(eval("@{}.set([1], 2)"), "@{[1]: 2}");
+    assert_eq!(eval("@{-1: 2, 3: 4}"), "@{-1: 2, 3: 4}");
+    let out = eval_lines("m = @{}.set(-1, 2)\nm\n@{-1: 2} == m\n@{} == @{}\n");
+    assert_eq!(out, ["@{-1: 2}", "@{-1: 2}", "true", "true"]);
+}
+
+#[test]
+fn whole_float_keys_match_integers() {
+    assert_eq!(eval(r#"{1: "a"}.get(1.0)"#), r#""a""#);
+    assert_eq!(eval(r#"{2.0: "a", 2: "b"}"#), r#"{2.0: "b"}"#);
+}
+
+#[test]
+fn large_maps() {
+    let out = eval_lines(
+        r#"fn(entry, (p), { [i, _] = p; f"\"{i}\": {i}" })
+body = [0].repeat(100000).enumerate.map(entry).join(", ")
+m = json_parse(f"{{{body}}}")
+m.len
+m.get("99999")
+"#,
+    );
+    assert_eq!(&out[3..], ["100000", "99999"]);
+}
+
+#[test]
+fn braces_without_a_key_are_still_blocks() {
+    assert_eq!(eval(r#"{ "a" }"#), r#""a""#);
+    assert_eq!(eval_lines("{}\n1\n"), ["1"]);
+}
+
+#[test]
+fn multi_line_literal() {
+    let out = eval_lines("m = {\n  \"a\": 1,\n  \"b\": [2, 3],\n}\nm\n");
+    assert_eq!(out[1], r#"{"a": 1, "b": [2, 3]}"#);
+}
+
+#[test]
+fn duplicate_keys_keep_their_first_place() {
+    assert_eq!(eval(r#"{"a": 1, "b": 2, "a": 3}"#), r#"{"a": 3, "b": 2}"#);
+}
+
+#[test]
+fn keys_values_and_items() {
+    assert_eq!(eval(r#"{"a": 1, "b": 2}.keys"#), r#"["a", "b"]"#);
+    assert_eq!(eval(r#"{"a": 1, "b": 2}.values"#), "[1, 2]");
+    assert_eq!(eval(r#"{"a": 1}.items"#), r#"[["a", 1]]"#);
+}
+
+#[test]
+fn get_and_set() {
+    assert_eq!(eval(r#"{"a": 1}.get("a")"#), "1");
+    assert_eq!(eval_lines("{\"a\": 1}.get(\"b\")\n0\n"), ["0"]);
+    assert_eq!(eval(r#"{"a": 1}.set("b", 2)"#), r#"{"a": 1, "b": 2}"#);
+    let out = eval_lines("m = {\"a\": 1}\nm.set(\"a\", 5)\nm\n");
+    assert_eq!(out[1..], [r#"{"a": 5}"#, r#"{"a": 1}"#]);
+}
+
+#[test]
+fn merge_prefers_the_right() {
+    assert_eq!(
+        eval(r#"{"a": 1, "b": 2} merge {"b": 3, "c": 4}"#),
+        r#"{"a": 1, "b": 3, "c": 4}"#
+    );
+}
+
+#[test]
+fn collections_see_key_value_pairs() {
+    assert_eq!(eval(r#"{"a": 1, "b": 2}.len"#), "2");
+    assert_eq!(
+        eval(r#"{"a": 1, "b": 2}.map(fn(second, (kv), { [k, v] = kv; v }))"#),
+        "[1, 2]"
+    );
+    assert_eq!(
+        eval(r#"{"a": 1, "b": 2}.filter(fn(big, (kv), { [k, v] = kv; v > 1 }))"#),
+        r#"{"b": 2}"#
+    );
+}
+
+#[test]
+fn equality_ignores_order() {
+    assert_eq!(eval(r#"{"a": 1, "b": 2} == {"b": 2, "a": 1}"#), "true");
+    assert_eq!(eval(r#"{"a": 1} == {"a": 2}"#), "false");
+}
+
+#[test]
+fn destructure_a_map() {
+    let out = eval_lines("{\"name\": n, \"tags\": [first, ..]} = {\"name\": \"x\", \"tags\": [1, 2], \"id\": 3}\n[n, first]\n");
+    assert_eq!(out[1], r#"["x", 1]"#);
+}
+
+#[test]
+fn missing_key_in_a_pattern() {
+    let out = repl("{\"a\": a} = {\"b\": 1}\n");
+    assert!(out.stderr.contains(r#"Missing key "a""#), "{}", out.stderr);
+}
+
+#[test]
+fn match_on_a_map() {
+    assert_eq!(
+        eval(
+            r#"match({"op": "neg", "x": 4}, {"op": "add"}, { 0 }, {"op": "neg", "x": x}, { 0 - x })"#
+        ),
+        "-4"
+    );
+}
+
+#[test]
+fn missing_colon() {
+    let out = repl("@{\"a\" 1}\n");
+    assert!(
+        out.stderr.contains("Expected `:` after a map key"),
+        "{}",
+        out.stderr
+    );
+}
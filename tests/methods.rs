@@ -0,0 +1,50 @@
+mod common;
+
+use common::{eval_lines, repl};
+
+const HEX_METHODS: &str = r#"zn(string, {
+    fn(hex, (s), { to_int(s, 16) })
+    fn(add_hex, (s, n), { hex(s) + n })
+})
+"#;
+
+#[test]
+fn arrow_dispatches_on_the_receiver_type() {
+    let out = eval_lines(&format!("{}\"ff\"->hex\n\"ff\"->add_hex(1)\n", HEX_METHODS));
+    assert_eq!(out, ["255", "256"]);
+}
+
+#[test]
+fn methods_are_bound_under_mangled_names() {
+    let out = eval_lines(&format!("{}__string__hex(\"10\")\n", HEX_METHODS));
+    assert_eq!(out, ["16"]);
+}
+
+#[test]
+fn namespaces_do_not_pollute_globals() {
+    // `hex` itself is also a builtin.
+    let out = repl(&format!("{}add_hex\n", HEX_METHODS));
+    assert!(out.stdout.is_empty());
+    assert!(out.stderr.contains("\"add_hex\" is not defined."));
+}
+
+#[test]
+fn each_type_has_its_own_methods() {
+    let out = repl(&format!(
+        "{}zn(number, {{ fn(hex, (n), {{ n + 1 }}) }})\n5->hex\n\"a\"->hex\n[1]->hex\n",
+        HEX_METHODS
+    ));
+    assert_eq!(out.stdout, "6\n10\n");
+    assert!(out.stderr.contains("No method \"hex\" for type array"));
+}
+
+#[test]
+fn namespaced_functions_do_not_replace_globals() {
+    let out = eval_lines(
+        r#"zn(string, { fn(to_int, (s), { 1 }) })
+to_int("ff", 16)
+"ff"->to_int
+"#,
+    );
+    assert_eq!(out, ["255", "1"]);
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::{eval, repl};
+
+#[test]
+fn float_literals() {
+    assert_eq!(eval("1.5"), "1.5");
+    assert_eq!(eval("1e3"), "1000.0");
+    assert_eq!(eval("0.5 + 0.25"), "0.75");
+}
+
+#[test]
+fn ints_and_floats_mix_as_floats() {
+    assert_eq!(eval("1 + 0.5"), "1.5");
+    assert_eq!(eval("2.5.x2"), "5.0");
+}
+
+#[test]
+fn overflow_promotes_to_a_big_integer() {
+    assert_eq!(eval("9223372036854775807 + 1"), "9223372036854775808");
+    assert_eq!(
+        eval("170141183460469231731687303715884105727.x2"),
+        "340282366920938463463374607431768211454"
+    );
+}
+
+#[test]
+fn negative_numbers_parse() {
+    assert_eq!(eval(r#"to_int("-ff", 16)"#), "-255");
+    assert_eq!(eval(r#"to_int("-1", 10) + 1"#), "0");
+}
+
+#[test]
+fn method_call_on_a_number() {
+    assert_eq!(eval("5.x2"), "10");
+}
+
+#[test]
+fn sum_mixes_numbers() {
+    assert_eq!(eval("sum([1, 2, 0.5])"), "3.5");
+}
+
+#[test]
+fn to_str_is_the_reverse_of_to_int() {
+    assert_eq!(eval("to_str(255, 16)"), r#""ff""#);
+    assert_eq!(eval("to_str(-5, 2)"), r#""-101""#);
+    assert_eq!(eval("to_str(255, 16).to_int(16)"), "255");
+    assert_eq!(eval("to_str(1.5, 10)"), r#""1.5""#);
+}
+
+#[test]
+fn prefixed_bases() {
+    assert_eq!(
+        eval("[255.hex, 5.bin, 8.oct]"),
+        r#"["0xff", "0b101", "0o10"]"#
+    );
+    assert_eq!(eval("-255 .hex"), r#""-0xff""#);
+    assert_eq!(eval("(2 ** 70).hex"), r#""0x400000000000000000""#);
+}
+
+#[test]
+fn parse_understands_prefixes() {
+    assert_eq!(
+        eval(r#"["42", "-0xff", "0b101", "0O17", " 1.5e3 "].map(parse)"#),
+        "[42, -255, 5, 15, 1500.0]"
+    );
+    assert_eq!(eval("[255.hex.parse, -6 .bin.parse]"), "[255, -6]");
+    let out = repl("\"nan\".parse\n\"0x\".parse\n\"12abc\".parse\n");
+    assert_eq!(
+        out.stderr.matches("is not a number").count(),
+        3,
+        "{}",
+        out.stderr
+    );
+}
+
+#[test]
+fn padding_and_grouping() {
+    assert_eq!(
+        eval("[7.zero_pad(3), -7 .zero_pad(4)]"),
+        r#"["007", "-007"]"#
+    );
+    assert_eq!(eval("to_str(5, 2).zero_pad(8)"), r#""00000101""#);
+    assert_eq!(
+        eval("[1234567.thousands, -1234.5 .thousands, 123.thousands]"),
+        r#"["1,234,567", "-1,234.5", "123"]"#
+    );
+}
+
+#[test]
+fn fixed_precision() {
+    assert_eq!(
+        eval("[3.14159.to_fixed(2), 2.to_fixed(1), 0.5.to_fixed(0)]"),
+        r#"["3.14", "2.0", "0"]"#
+    );
+}
+
+#[test]
+fn fixed_precision_is_limited() {
+    let out = repl("3.0.to_fixed(70000)\n5e-324.to_fixed(1074).len\n");
+    assert!(
+        out.stderr
+            .contains("Can't show 70000 digits, the most is 1074"),
+        "{}",
+        out.stderr
+    );
+    assert_eq!(out.stdout, "1076\n");
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use common::{eval, eval_lines, repl};
+
+#[test]
+fn arithmetic() {
+    assert_eq!(eval("7 - 10"), "-3");
+    assert_eq!(eval("6 * 7"), "42");
+    assert_eq!(eval("7 / 2"), "3.5");
+    assert_eq!(eval("7 // 2"), "3");
+    assert_eq!(eval("-7 // 2"), "-4");
+    assert_eq!(eval("2 ** 10"), "1024");
+    assert_eq!(eval("2 ** -1"), "0.5");
+    assert_eq!(eval("-7 mod 3"), "2");
+}
+
+#[test]
+fn division_by_zero_is_an_error() {
+    let out = repl("1 // 0\n");
+    assert!(out.stderr.contains("Division by zero"), "{}", out.stderr);
+}
+
+#[test]
+fn comparison() {
+    assert_eq!(eval("1 == 1.0"), "true");
+    assert_eq!(eval(r#""a" != "b""#), "true");
+    assert_eq!(eval("[1, 2] == [1, 2]"), "true");
+    assert_eq!(eval("2 < 10"), "true");
+    assert_eq!(eval(r#""b" <= "a""#), "false");
+    assert_eq!(eval("[1, 3] > [1, 2, 5]"), "true");
+    assert_eq!(eval("2 >= 2"), "true");
+}
+
+#[test]
+fn comparing_different_types_is_an_error() {
+    let out = repl("1 < \"a\"\n");
+    assert!(
+        out.stderr.contains(r#"Can't compare 1 with "a""#),
+        "{}",
+        out.stderr
+    );
+}
+
+#[test]
+fn logic() {
+    assert_eq!(eval(r#"1 && """#), "false");
+    assert_eq!(eval("0 || [1]"), "true");
+    assert_eq!(eval("!(0)"), "true");
+    assert_eq!(eval("[0, 1].map(!)"), "[true, false]");
+}
+
+#[test]
+fn bitwise() {
+    assert_eq!(eval("12 & 10"), "8");
+    assert_eq!(eval("12 | 3"), "15");
+    assert_eq!(eval("12 ^ 10"), "6");
+    assert_eq!(eval("1 << 70"), "1180591620717411303424");
+    assert_eq!(eval("-16 >> 2"), "-4");
+}
+
+#[test]
+fn operators_are_partially_applicable() {
+    assert_eq!(eval("[1, 2, 3].map(*(,10))"), "[10, 20, 30]");
+    assert_eq!(eval("[1, 5, 9].filter(>(,4))"), "[5, 9]");
+    assert_eq!(eval("[1, 2, 3].reduce(-)"), "-4");
+}
+
+#[test]
+fn negative_literals() {
+    assert_eq!(eval("[-1, -2.5]"), "[-1, -2.5]");
+    assert_eq!(eval("3 -1"), "2");
+}
+
+#[test]
+fn negating_any_operand() {
+    let out = eval_lines("a = 3\n-a\n[1, -a, - (a * 2)]\n2 - -a\n-\"x\"\n");
+    assert_eq!(out, ["3", "-3", "[1, -3, -6]", "5"]);
+    assert_eq!(eval("[5, 2].reduce(-)"), "3");
+}
+
+#[test]
+fn huge_shifts_and_powers_fail() {
+    let out = repl("1 << 99999999999999\n2 ** 4000000000\n0 << 99999999999999\n1 ** 4000000000\n");
+    assert!(
+        out.stderr.contains("Shift 99999999999999 is too large"),
+        "{}",
+        out.stderr
+    );
+    assert!(
+        out.stderr.contains("Exponent 4000000000 is too large"),
+        "{}",
+        out.stderr
+    );
+    assert_eq!(out.stdout, "0\n1\n");
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use common::{eval_lines, repl};
+use xur::repl::Repl;
+
+fn render_error(source: &str) -> String {
+    let mut repl = Repl::new();
+    let err = repl.eval(source).expect_err("source should fail");
+    repl.report(&err)
+}
+
+#[test]
+fn destructure_an_array() {
+    let out = eval_lines("[a, b] = [1, 2]\na + b\n");
+    assert_eq!(out, ["[1, 2]", "3"]);
+}
+
+#[test]
+fn rest_of_an_array() {
+    let out = eval_lines("[first, ..rest] = [1, 2, 3]\nfirst\nrest\n");
+    assert_eq!(out[1..], ["1", "[2, 3]"]);
+    let out = eval_lines("[..init, last] = [1, 2, 3]\ninit\nlast\n");
+    assert_eq!(out[1..], ["[1, 2]", "3"]);
+    let out = eval_lines("[a, .., z] = [1, 2, 3, 4]\n[a, z]\n");
+    assert_eq!(out[1..], ["[1, 4]"]);
+}
+
+#[test]
+fn nested_patterns_wildcards_and_literals() {
+    let out = eval_lines("[[x, _], \"y\", ..] = [[1, 2], \"y\", 3]\nx\n");
+    assert_eq!(out[1..], ["1"]);
+}
+
+#[test]
+fn const_destructuring() {
+    let out = repl("const [a, b] = [1, 2]\nb = 3\n");
+    assert!(
+        out.stderr
+            .contains(r#"Cannot assign twice to constant "b""#),
+        "{}",
+        out.stderr
+    );
+}
+
+#[test]
+fn wrong_length() {
+    assert_eq!(
+        render_error("[a, b] = [1, 2, 3]"),
+        r#"error: Expected an array of 2 elements, got 3
+ --> <repl>:1:1
+  |
+1 | [a, b] = [1, 2, 3]
+  | ^^^^^^ pattern doesn't match
+"#
+    );
+}
+
+#[test]
+fn nested_mismatch_points_at_the_inner_pattern() {
+    assert_eq!(
+        render_error("[a, [b, c]] = [1, 2]"),
+        r#"error: Expected an array, got number 2
+ --> <repl>:1:5
+  |
+1 | [a, [b, c]] = [1, 2]
+  |     ^^^^^^ pattern doesn't match
+"#
+    );
+}
+
+#[test]
+fn nothing_is_bound_if_the_pattern_fails() {
+    let out = repl("[a, 2] = [1, 3]\na\n");
+    assert!(out.stderr.contains("Expected 2, got 3"), "{}", out.stderr);
+    assert!(
+        out.stderr.contains(r#""a" is not defined"#),
+        "{}",
+        out.stderr
+    );
+}
+
+#[test]
+fn invalid_patterns() {
+    let out = repl("[a, x2(b)] = [1, 2]\n[..a, ..b] = [1]\n");
+    assert!(out.stderr.contains("Invalid pattern"), "{}", out.stderr);
+    assert!(
+        out.stderr.contains("Only one `..` is allowed"),
+        "{}",
+        out.stderr
+    );
+}
+
+#[test]
+fn spread_into_an_array() {
+    let out = eval_lines("xs = [2, 3]\n[1, ..xs, 4]\n");
+    assert_eq!(out[1..], ["[1, 2, 3, 4]"]);
+}
+
+#[test]
+fn match_on_shape() {
+    let out = eval_lines(
+        "fn(describe, (v), {\n\
+             match(v,\n\
+                 [], { \"empty\" },\n\
+                 [x], { \"one\" },\n\
+                 [\"add\", a, b], { a + b },\n\
+                 [x, ..rest], { rest.len },\n\
+                 _, { \"other\" }\n\
+             )\n\
+         })\n\
+         [[], [1], [\"add\", 2, 3], [1, 2, 3], 5].map(describe)\n",
+    );
+    assert_eq!(out[1..], [r#"["empty", "one", 5, 2, "other"]"#]);
+}
+
+#[test]
+fn match_bindings_stay_in_the_arm() {
+    let out = repl("match([1], [a], { a })\na\n");
+    assert_eq!(out.stdout, "1\n");
+    assert!(
+        out.stderr.contains(r#""a" is not defined"#),
+        "{}",
+        out.stderr
+    );
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::{eval, eval_lines, script};
+
+#[test]
+fn multiplication_binds_tighter_than_addition() {
+    assert_eq!(eval("1 + 2 * 3"), "7");
+    assert_eq!(eval("2 * 3 + 1"), "7");
+    assert_eq!(eval("1 + 2 * 3 ** 2"), "19");
+}
+
+#[test]
+fn left_associative() {
+    assert_eq!(eval("10 - 2 - 3"), "5");
+    assert_eq!(eval("100 // 10 // 5"), "2");
+}
+
+#[test]
+fn power_is_right_associative() {
+    assert_eq!(eval("2 ** 3 ** 2"), "512");
+}
+
+#[test]
+fn comparison_and_logic_bind_loosely() {
+    assert_eq!(eval("1 + 2 == 3"), "true");
+    assert_eq!(eval("1 < 2 && 3 < 2 || 4 == 4"), "true");
+    assert_eq!(eval("1 | 2 == 3"), "true");
+}
+
+#[test]
+fn named_functions_bind_tighter_than_operators() {
+    assert_eq!(eval(r#""FF" to_int 16 + 1"#), "256");
+    assert_eq!(eval(r#"1 + "FF" to_int 16"#), "256");
+}
+
+#[test]
+fn calls_and_methods_bind_to_their_operand() {
+    assert_eq!(eval("1 + [1, 2, 3].map(*(,2)).sum * 2"), "25");
+    assert_eq!(eval("2 * x2(3)"), "12");
+}
+
+#[test]
+fn user_defined_precedence() {
+    let out = eval_lines(
+        "fn(plus, (a, b), { a + b })\n\
+         2 * 3 plus 1\n\
+         infixl(\"plus\", 8)\n\
+         2 * 3 plus 1\n",
+    );
+    assert_eq!(out, ["<fn plus>", "8", "7"]);
+}
+
+#[test]
+fn user_defined_associativity() {
+    let out = eval_lines(
+        "fn(minus, (a, b), { a - b })\n\
+         infixr(\"minus\", 8)\n\
+         10 minus 4 minus 3\n",
+    );
+    assert_eq!(out, ["<fn minus>", "9"]);
+}
+
+#[test]
+fn precedence_applies_to_the_next_statement() {
+    let out = eval_lines("fn(plus, (a, b), { a + b }); infixl(\"plus\", 8); 2 * 3 plus 1\n");
+    assert_eq!(out, ["7"]);
+}
+
+#[test]
+fn inline_call_without_a_right_side() {
+    assert_eq!(
+        eval_lines("3 x2\n[2 x2, 1]\n(1 +)(2)\n"),
+        ["6", "[4, 1]", "3"]
+    );
+}
+
+#[test]
+fn inline_call_without_a_right_side_ends_at_the_line() {
+    let out = script("y = 1\nx = 5 x2\ny\nx\n");
+    assert_eq!(out.stdout, "1\n10\n1\n10\n");
+    assert_eq!(out.stderr, "");
+}
+
+#[test]
+fn inline_call_without_a_right_side_in_a_block() {
+    let out = script(
+        "b = {\n    y = 3 x2\n    y + 1\n}\nb\n\
+         fn(g, (n), { a = n x2\n    a + 1 })\ng(4)\n",
+    );
+    assert_eq!(out.stdout, "7\n7\n<fn g>\n9\n");
+    assert_eq!(out.stderr, "");
+}
+
+#[test]
+fn operator_at_the_end_of_a_line_carries_on() {
+    let out = script("x = 1 +\n    2 *\n    3\nx\n");
+    assert_eq!(out.stdout, "7\n7\n");
+}
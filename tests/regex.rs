@@ -0,0 +1,62 @@
+mod common;
+
+use common::{eval, eval_lines, repl};
+
+#[test]
+fn regex_values() {
+    assert_eq!(eval(r#"re("\\d+")"#), r#"re("\\d+")"#);
+    assert_eq!(eval(r#"re("a") == re("a")"#), "true");
+}
+
+#[test]
+fn many_generated_patterns() {
+    let out = eval_lines(
+        r#"fn(check, (p), { [i, _] = p; f"x{i}y" matches f"^x{i}y$" })
+[0].repeat(100).enumerate.all(check)
+"x1y" matches "^x1y$"
+"#,
+    );
+    assert_eq!(&out[1..], ["true", "true"]);
+}
+
+#[test]
+fn partial_matcher_filters() {
+    assert_eq!(
+        eval(r#"["a1", "b", "c22"].filter(matches(,re("\\d+")))"#),
+        r#"["a1", "c22"]"#
+    );
+    assert_eq!(eval(r#""abc" matches r"^a""#), "true");
+}
+
+#[test]
+fn captures() {
+    assert_eq!(
+        eval(r#""k=v".captures(r"(\w)=(\w)(x)?")"#),
+        r#"["k=v", "k", "v", none]"#
+    );
+    assert_eq!(
+        eval(r#""level=warn".captures(r"(?P<key>\w+)=(?P<value>\w+)")"#),
+        r#"{"key": "level", "value": "warn"}"#
+    );
+    assert_eq!(eval(r#""abc".captures(r"\d") == none"#), "true");
+}
+
+#[test]
+fn find_replace_and_split() {
+    assert_eq!(eval(r#""x=1 y=22".find_all(r"\d+")"#), r#"["1", "22"]"#);
+    assert_eq!(
+        eval(r#""a=1, b=2".re_replace(r"(\w)=(\d)", "$2=$1")"#),
+        r#""1=a, 2=b""#
+    );
+    assert_eq!(eval(r#""a, b,c".re_split(r",\s*")"#), r#"["a", "b", "c"]"#);
+}
+
+#[test]
+fn invalid_patterns_are_reported() {
+    let out = repl("re(\"(\")\n");
+    assert!(
+        out.stderr.contains(r#"Invalid regex "(": unclosed group"#),
+        "{}",
+        out.stderr
+    );
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::{eval_lines, repl};
+use xur::repl::Repl;
+
+#[test]
+fn readme_calls_are_equivalent() {
+    let out = eval_lines(
+        r#"to_int("FF", 16)
+"FF" to_int 16
+to_int(,16)("FF")
+"FF".to_int(16)
+"#,
+    );
+    assert_eq!(out, ["255", "255", "255", "255"]);
+}
+
+#[test]
+fn prints_values() {
+    let out = eval_lines(
+        r#"1 + 2
+"abc"
+["a", 1]
+to_int
+"#,
+    );
+    assert_eq!(out, ["3", "\"abc\"", "[\"a\", 1]", "<fn to_int>"]);
+}
+
+#[test]
+fn prompts_only_when_asked() {
+    let run = |mut repl: Repl| {
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        repl.run("[1,\n2]\n".as_bytes(), &mut out, &mut err)
+            .expect("run failed");
+        (
+            String::from_utf8(out).unwrap(),
+            String::from_utf8(err).unwrap(),
+        )
+    };
+    assert_eq!(
+        run(Repl::new().with_prompt(true)),
+        ("[1, 2]\n".to_string(), "> ... > ".to_string())
+    );
+    assert_eq!(run(Repl::new()), ("[1, 2]\n".to_string(), String::new()));
+}
+
+#[test]
+fn keeps_going_after_an_error() {
+    let out = repl("nope\n1 + 1\n");
+    assert_eq!(out.stdout, "2\n");
+    assert!(out.stderr.contains("\"nope\" is not defined."));
+}
+
+#[test]
+fn skips_blank_lines() {
+    assert_eq!(eval_lines("\n  \n1 + 1\n\n"), ["2"]);
+}
+
+#[test]
+fn skips_comments() {
+    let out = eval_lines("# a whole line\n1 + 2 # trailing\n#[ spans\nlines ]# 4\n[1, # one\n2]\n");
+    assert_eq!(out, ["3", "4", "[1, 2]"]);
+}
+
+#[test]
+fn unterminated_block_comment() {
+    let out = repl("1 #[ open\n");
+    assert!(
+        out.stderr.contains("Unterminated block comment"),
+        "{}",
+        out.stderr
+    );
+}
+
+#[test]
+fn multiline_strings_continue_onto_the_next_line() {
+    let out = eval_lines("\"\"\"\none\ntwo\"\"\".len\n");
+    assert_eq!(out, ["7"]);
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use common::{eval_lines, repl};
+
+#[test]
+fn returned_functions_close_over_parameters() {
+    let out = eval_lines(
+        r#"fn(make_adder, (n), { fn(adder, (x), { x + n }) })
+add5 = make_adder(5)
+add5(1)
+make_adder(1)(2)
+"#,
+    );
+    assert_eq!(out, ["<fn make_adder>", "<fn adder>", "6", "3"]);
+}
+
+#[test]
+fn partial_functions_outlive_their_scope() {
+    let out = eval_lines(
+        r#"fn(add, (a, b), { a + b })
+fn(partial, (n), { add(n) })
+p = partial(7)
+p(1)
+"#,
+    );
+    assert_eq!(out[3], "8");
+}
+
+#[test]
+fn functions_returned_from_blocks_keep_the_block() {
+    let out = repl("g = { y = 2; fn(gy, (x), { x + y }) }\ng(1)\ny\n");
+    assert_eq!(out.stdout, "<fn gy>\n3\n");
+    assert!(out.stderr.contains("\"y\" is not defined."));
+}
+
+#[test]
+fn inner_definitions_stay_inner() {
+    let out = repl("fn(outer, (), { fn(inner, (), { 1 }) })\nouter()\ninner\n");
+    assert_eq!(out.stdout, "<fn outer>\n<fn inner>\n");
+    assert!(out.stderr.contains("\"inner\" is not defined."));
+}
+
+#[test]
+fn assignment_in_a_function_is_local() {
+    let out = eval_lines(
+        r#"x = 1
+fn(f, (), { x = 2; x })
+f()
+x
+"#,
+    );
+    assert_eq!(out, ["1", "<fn f>", "2", "1"]);
+}
+
+#[test]
+fn assignment_in_a_block_updates_the_function() {
+    let out = eval_lines(
+        r#"total = 0
+fn(f, (n), { total = 1; if(n > 0, { total = total + n }); total })
+f(5)
+total
+{ total = 3 }
+total
+"#,
+    );
+    assert_eq!(&out[2..], ["6", "0", "3", "3"]);
+}
+
+#[test]
+fn nonlocal_updates_the_enclosing_binding() {
+    let out = eval_lines(
+        r#"count = 0
+fn(inc, (), { nonlocal count = count + 1 })
+inc()
+inc()
+count
+"#,
+    );
+    assert_eq!(out, ["0", "<fn inc>", "1", "2", "2"]);
+}
+
+#[test]
+fn nonlocal_needs_an_existing_binding() {
+    let out = repl(
+        "fn(f, (), { nonlocal nope = 1 })
+f()
+nope
+",
+    );
+    assert!(
+        out.stderr
+            .contains("\"nope\" is not defined.\n --> <repl>:1:22"),
+        "{}",
+        out.stderr
+    );
+    assert!(out.stderr.contains("`nonlocal` needs an existing binding"));
+    let out = repl("const k = 1\nfn(f, (), { nonlocal k = 2 })\nf()\n");
+    assert!(out.stderr.contains("Cannot assign twice to constant \"k\""));
+}
+
+#[test]
+fn closures_share_captured_state() {
+    let out = eval_lines(
+        r#"fn(counter, (), {
+    n = 0
+    fn(next, (), { nonlocal n = n + 1 })
+})
+a = counter()
+b = counter()
+a()
+a()
+b()
+"#,
+    );
+    assert_eq!(&out[3..], ["1", "2", "1"]);
+}
+
+#[test]
+fn constants_shadow_in_inner_scopes() {
+    let out = repl("const k = 1\n{ const k = 2; k }\nk\n");
+    assert_eq!(out.stdout, "1\n2\n1\n");
+}
@@ -0,0 +1,59 @@
+use xur::ast::value::{ExpressionKind, Statement};
+use xur::lexer::{Span, TokenKind, Tokenizer};
+use xur::repl::Repl;
+
+fn span(start: usize, end: usize, line: usize, col: usize) -> Span {
+    Span {
+        start,
+        end,
+        line,
+        col,
+    }
+}
+
+#[test]
+fn tokens_track_offset_line_and_column() {
+    let tokens: Vec<_> = Tokenizer::new("to_int(\n  \"ff\", 16)")
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Newline))
+        .map(|t| t.span)
+        .collect();
+
+    assert_eq!(
+        tokens,
+        [
+            span(0, 6, 1, 1),
+            span(6, 7, 1, 7),
+            span(10, 14, 2, 3),
+            span(14, 15, 2, 7),
+            span(16, 18, 2, 9),
+            span(18, 19, 2, 11),
+        ]
+    );
+}
+
+#[test]
+fn columns_count_characters_not_bytes() {
+    let tokens: Vec<_> = Tokenizer::new("\"é\" x").map(|t| t.span).collect();
+    assert_eq!(tokens[2], span(5, 6, 1, 5));
+}
+
+#[test]
+fn expressions_cover_their_source() {
+    let source = "1 + to_int(\"ff\", 16)";
+    let statement = Repl::parse(source).next().unwrap().unwrap();
+    let ex = match statement {
+        Statement::Expr(ex) => ex,
+        s => panic!("expected an expression, got {:?}", s),
+    };
+    assert_eq!(&source[ex.span.start..ex.span.end], source);
+
+    let args = match ex.kind {
+        ExpressionKind::Call { args, .. } => args,
+        k => panic!("expected a call, got {:?}", k),
+    };
+    let spans: Vec<_> = args
+        .iter()
+        .map(|a| &source[a.span.start..a.span.end])
+        .collect();
+    assert_eq!(spans, ["1", "to_int(\"ff\", 16)"]);
+}
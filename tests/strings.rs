@@ -0,0 +1,145 @@
+mod common;
+
+use common::{eval, eval_lines, repl};
+
+#[test]
+fn format_strings_fill_in_holes() {
+    let out = eval_lines("name = \"xur\"; count = 3\nf\"{name}: {count x2}\"\n");
+    assert_eq!(out[1], r#""xur: 6""#);
+}
+
+#[test]
+fn format_strings_display_values() {
+    assert_eq!(
+        eval(r#"f"{1.5} {true} {none} {b"a"}""#),
+        r#""1.5 true none b\"a\"""#
+    );
+    assert_eq!(
+        eval(r#"f"{[1, "a"]} { {"k": "v"} }""#),
+        r#""[1, \"a\"] {\"k\": \"v\"}""#
+    );
+}
+
+#[test]
+fn format_string_holes_are_expressions() {
+    assert_eq!(
+        eval(r#"f"{{x}} {1 + 2} {[1, 2].map(x2)}""#),
+        r#""{x} 3 [2, 4]""#
+    );
+    assert_eq!(eval(r#"f"{"}"}\t""#), r#""}\t""#);
+}
+
+#[test]
+fn format_string_errors() {
+    let out = repl("f\"{nope}\"\n");
+    assert!(
+        out.stderr
+            .contains("1 | f\"{nope}\"\n  |    ^^^^ not found"),
+        "{}",
+        out.stderr
+    );
+
+    let out = repl("f\"{}\"\nf\"{1 2}\"\nf\"a } b\"\nf\"{1\n");
+    assert!(
+        out.stderr.contains("Empty `{}` in format string"),
+        "{}",
+        out.stderr
+    );
+    assert!(
+        out.stderr.contains("Unexpected token in format string"),
+        "{}",
+        out.stderr
+    );
+    assert!(
+        out.stderr.contains("Unmatched `}` in format string"),
+        "{}",
+        out.stderr
+    );
+    assert!(
+        out.stderr.contains("Unclosed `{` in format string"),
+        "{}",
+        out.stderr
+    );
+}
+
+#[test]
+fn split_and_join() {
+    let out = eval_lines("text = \"a,1\\nb,2\"\ntext.lines.map(split(,\",\"))\n");
+    assert_eq!(out[1], r#"[["a", "1"], ["b", "2"]]"#);
+    assert_eq!(eval(r#"["a", 1, [2]].join(", ")"#), r#""a, 1, [2]""#);
+    assert_eq!(eval(r#""a b" split " " join "_""#), r#""a_b""#);
+}
+
+#[test]
+fn case_and_whitespace() {
+    assert_eq!(
+        eval(r#"["  Hi ".trim, "Hi".lower, "Hi".upper]"#),
+        r#"["Hi", "hi", "HI"]"#
+    );
+    assert_eq!(
+        eval(r#"["7".pad_left(3), "7".pad_right(3)]"#),
+        r#"["  7", "7  "]"#
+    );
+    assert_eq!(eval(r#""long".pad_left(2)"#), r#""long""#);
+}
+
+#[test]
+fn searching() {
+    assert_eq!(
+        eval(r#"["hello" starts_with "he", "hello".ends_with("he"), "hello" contains "ell"]"#),
+        "[true, false, true]"
+    );
+    assert_eq!(eval(r#"["héllo".find("l"), [1, 2, 3].find(3)]"#), "[2, 2]");
+    assert_eq!(eval(r#""hello".find("z") == none"#), "true");
+    assert_eq!(eval(r#""a-b-c".replace("-", "+")"#), r#""a+b+c""#);
+}
+
+#[test]
+fn slicing() {
+    assert_eq!(eval(r#""hello".slice(1, 3)"#), r#""el""#);
+    assert_eq!(eval(r#""hello".slice(-3, none)"#), r#""llo""#);
+    assert_eq!(eval("[1, 2, 3, 4].slice(1, -1)"), "[2, 3]");
+    assert_eq!(eval("[1, 2].slice(5, 1)"), "[]");
+}
+
+#[test]
+fn chars_repeat_and_reverse() {
+    assert_eq!(eval(r#""hé".chars"#), r#"["h", "é"]"#);
+    assert_eq!(
+        eval(r#"["ab".repeat(3), "abc".reverse]"#),
+        r#"["ababab", "cba"]"#
+    );
+    assert_eq!(eval("[1, 2].repeat(2).reverse"), "[2, 1, 2, 1]");
+}
+
+#[test]
+fn string_functions_are_partial() {
+    assert_eq!(
+        eval(r#"["a", "bb"].map(pad_left(,3)).map(replace(," ","."))"#),
+        r#"["..a", ".bb"]"#
+    );
+    assert_eq!(
+        eval(r#"["ab", "cd"].filter(starts_with(,"c"))"#),
+        r#"["cd"]"#
+    );
+}
+
+#[test]
+fn splitting_on_nothing_fails() {
+    let out = repl("\"ab\".split(\"\")\n");
+    assert!(out.stderr.contains("use `chars` instead"), "{}", out.stderr);
+}
+
+#[test]
+fn repeating_too_much_fails() {
+    let out =
+        repl("\"abc\".repeat(3074457345618258603)\n\"abc\".repeat(2)\n[0].repeat(16777217)\n");
+    assert!(out.stderr.contains("is too many"), "{}", out.stderr);
+    assert!(
+        out.stderr
+            .contains("Repeating 1 items 16777217 times is too many, the most is 16777216"),
+        "{}",
+        out.stderr
+    );
+    assert_eq!(out.stdout, "\"abcabc\"\n");
+}
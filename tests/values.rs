@@ -0,0 +1,134 @@
+mod common;
+
+use common::{eval, eval_lines, repl};
+
+#[test]
+fn tuples() {
+    assert_eq!(eval("@(1, \"a\")"), r#"@(1, "a")"#);
+    assert_eq!(eval("@(1)"), "@(1)");
+    assert_eq!(eval("@()"), "@()");
+    assert_eq!(eval("[@(1, 2), @(3, 4)].len"), "2");
+    assert_eq!(eval("[1, 2].to_tuple"), "@(1, 2)");
+}
+
+#[test]
+fn parentheses_around_one_value_group() {
+    assert_eq!(eval("(1 + 2) * 3"), "9");
+    assert_eq!(eval("x = (5)"), "5");
+}
+
+#[test]
+fn parameter_sets_are_not_values() {
+    let out = repl(
+        "x = (1, 2)
+",
+    );
+    assert!(
+        out.stderr
+            .contains("A parameter set can only be used as arguments"),
+        "{}",
+        out.stderr
+    );
+    assert!(
+        out.stderr.contains("use `@(...)` for a tuple"),
+        "{}",
+        out.stderr
+    );
+}
+
+#[test]
+fn tuples_compare() {
+    assert_eq!(eval("@(1, 2) == @(1, 2)"), "true");
+    assert_eq!(eval("@(1, 2) == [1, 2]"), "false");
+    assert_eq!(eval("@(1, 2) < @(1, 3)"), "true");
+}
+
+#[test]
+fn destructure_a_tuple() {
+    let out = eval_lines(
+        "(a, b) = @(1, 2)
+@(x, y) = [3, 4]
+[a, b, x, y]
+",
+    );
+    assert_eq!(out[2], "[1, 2, 3, 4]");
+}
+
+#[test]
+fn sets() {
+    assert_eq!(eval("to_set([1, 2, 2, 1])"), "to_set([1, 2])");
+    assert_eq!(eval("to_set([1, 2]) == to_set([2, 1])"), "true");
+    assert_eq!(eval("[1, 2] union [2, 3]"), "to_set([1, 2, 3])");
+    assert_eq!(eval("[1, 2, 3] intersect [3, 2]"), "to_set([2, 3])");
+    assert_eq!(eval("[1, 2, 3] diff [2]"), "to_set([1, 3])");
+    assert_eq!(eval(r#""hello".to_set.len"#), "4");
+}
+
+#[test]
+fn set_items_dedupe_by_value() {
+    assert_eq!(
+        eval("to_set([1, 1.0, 2.0, 2, -0.0, 0])"),
+        "to_set([1, 2.0, -0.0])"
+    );
+    assert_eq!(eval("to_set([to_set([1, 2]), to_set([2, 1])]).len"), "1");
+    assert_eq!(
+        eval(r#"to_set([{"a": 1, "b": 2}, {"b": 2, "a": 1}]).len"#),
+        "1"
+    );
+    assert_eq!(eval("to_set([[1, 2], @(1, 2), [1, 2.0]]).len"), "2");
+}
+
+#[test]
+fn large_sets() {
+    assert_eq!(eval("[0].repeat(100000).enumerate.to_set.len"), "100000");
+    assert_eq!(
+        eval("([0].repeat(50000).enumerate intersect [0].repeat(60000).enumerate).len"),
+        "50000"
+    );
+}
+
+#[test]
+fn filtering_keeps_the_collection_type() {
+    assert_eq!(eval("to_set([1, 2, 3]).filter(>(,1))"), "to_set([2, 3])");
+    assert_eq!(eval("@(1, 2, 3).filter(>(,1))"), "@(2, 3)");
+    assert_eq!(eval(r#"b"a1b2".filter(<(,64))"#), r#"b"12""#);
+}
+
+#[test]
+fn contains() {
+    assert_eq!(eval("[1, 2].contains(2)"), "true");
+    assert_eq!(eval("to_set([1]).contains(2)"), "false");
+    assert_eq!(eval(r#"{"a": 1}.contains("a")"#), "true");
+}
+
+#[test]
+fn bytes() {
+    assert_eq!(eval(r#"b"hi\x00\xff""#), r#"b"hi\x00\xff""#);
+    assert_eq!(eval(r#"b"hi".to_array"#), "[104, 105]");
+    assert_eq!(eval("[104, 105].to_bytes"), r#"b"hi""#);
+    assert_eq!(eval(r#"b"\x01\xff".len"#), "2");
+}
+
+#[test]
+fn bytes_strings_and_hex() {
+    assert_eq!(eval(r#""héllo".encode"#), r#"b"h\xc3\xa9llo""#);
+    assert_eq!(eval(r#""héllo".encode.decode"#), r#""héllo""#);
+    assert_eq!(eval(r#"b"\x01\xff".to_hex"#), r#""01ff""#);
+    assert_eq!(eval(r#""01FF".from_hex"#), r#"b"\x01\xff""#);
+    assert_eq!(
+        eval(r#"["6869", "7879"].map(from_hex).map(decode)"#),
+        r#"["hi", "xy"]"#
+    );
+}
+
+#[test]
+fn conversion_errors() {
+    let out = repl("[256].to_bytes\n\"abc\".from_hex\nb\"\\xff\".decode\n");
+    assert!(out.stderr.contains("256 is not a byte"), "{}", out.stderr);
+    assert!(
+        out.stderr.contains("is not an even number of hex digits"),
+        "{}",
+        out.stderr
+    );
+    assert!(out.stderr.contains("not valid utf-8"), "{}", out.stderr);
+}